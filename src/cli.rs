@@ -0,0 +1,413 @@
+use clap::{Parser, Subcommand};
+use iota_challenge3_example::{
+    ChallengeConfig, ChallengeError, DEFAULT_GAS_MULTIPLIER, DEFAULT_MAX_ATTEMPTS, FileConfig, Network,
+    RpcHeader, SenderSelection, WaitStrategy,
+    config::{DeploymentManifest, parse_http_url, parse_identifier, parse_ws_url, ws_url_for},
+    runner::{DEFAULT_COIN_TIMEOUT_SECS, DEFAULT_RUN_TIMEOUT_SECS},
+};
+use iota_sdk::types::base_types::{IotaAddress, ObjectID};
+use move_core_types::language_storage::StructTag;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Solve IOTA Move CTF challenge #3 by minting, merging and splitting MINTCOINs.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Args {
+    #[command(subcommand)]
+    pub command: Command,
+
+    /// TOML file with defaults for the endpoint, object IDs, mint count and flag coin value
+    #[arg(long, global = true)]
+    pub config: Option<PathBuf>,
+
+    /// TOML or JSON file with the package_id, treasury_cap_id and shared_counter_id of a deployment, e.g. written by
+    /// a deploy step; --package-id and friends override single IDs
+    #[arg(long, global = true)]
+    pub manifest: Option<PathBuf>,
+
+    /// Network whose RPC endpoint and faucet to use: testnet, devnet, mainnet or localnet [default: testnet, without
+    /// the faucet]
+    #[arg(long, global = true)]
+    pub network: Option<Network>,
+
+    /// JSON-RPC endpoint of the IOTA node; overrides the one of --network [default: https://api.testnet.iota.cafe].
+    /// Repeat to fail over to the next endpoint whenever the current one can't be reached
+    #[arg(long = "rpc-url", global = true, value_parser = parse_http_url)]
+    pub rpc_urls: Vec<String>,
+
+    /// HTTP header sent with every RPC request, e.g. "X-Api-Key: <key>"; repeatable
+    #[arg(long = "rpc-header", global = true, value_name = "NAME: VALUE")]
+    pub rpc_headers: Vec<RpcHeader>,
+
+    /// WebSocket endpoint for --watch-flag-event [default: the RPC URL with ws:// or wss://]; given explicitly,
+    /// run-all also waits for the effects of transaction 1 over it instead of polling for the minted coins
+    #[arg(long, global = true, value_parser = parse_ws_url)]
+    pub ws_url: Option<String>,
+
+    /// Seconds an RPC request, or connecting to the node, may take before failing [default: 30]
+    #[arg(long, global = true, value_parser = clap::value_parser!(u64).range(1..))]
+    pub request_timeout: Option<u64>,
+
+    /// Package that publishes the `mintcoin` module [default: the testnet deployment]
+    #[arg(long, global = true, value_parser = parse_object_id)]
+    pub package_id: Option<ObjectID>,
+
+    /// Shared `TreasuryCap` used by `mint_coin` [default: the testnet deployment]
+    #[arg(long, global = true, value_parser = parse_object_id)]
+    pub treasury_cap_id: Option<ObjectID>,
+
+    /// Shared counter passed to `get_flag` [default: the testnet deployment]
+    #[arg(long, global = true, value_parser = parse_object_id)]
+    pub shared_counter_id: Option<ObjectID>,
+
+    /// Module of the package defining the challenge coin, for forks that renamed it [default: mintcoin]
+    #[arg(long, global = true, value_parser = parse_identifier)]
+    pub coin_module: Option<String>,
+
+    /// Struct naming the challenge coin type inside --coin-module [default: MINTCOIN]
+    #[arg(long, global = true, value_parser = parse_identifier)]
+    pub coin_struct: Option<String>,
+
+    /// Treat `get_flag` as consuming the flag coin, so it is not transferred back afterwards [default: detected from
+    /// the deployed signature]
+    #[arg(long, global = true)]
+    pub flag_consumes_coin: bool,
+
+    /// Keystore file to sign with; repeat to merge the addresses of several, each signing with its own keys
+    /// [default: $IOTA_KEYSTORE_PATH, then ~/.iota/iota_config/iota.keystore]
+    #[arg(long, global = true, visible_alias = "keystore")]
+    pub keystore_path: Vec<PathBuf>,
+
+    /// Keystore address to sign with; defaults to the first one
+    #[arg(long, global = true, visible_alias = "address", value_parser = parse_address)]
+    pub sender: Option<IotaAddress>,
+
+    /// Position of the keystore address to sign with, starting at 0
+    #[arg(long, global = true, conflicts_with = "sender")]
+    pub address_index: Option<usize>,
+
+    /// Coin type to pay gas with, for networks whose gas coin isn't 0x2::iota::IOTA
+    #[arg(long, global = true, value_parser = parse_coin_type)]
+    pub gas_coin_type: Option<String>,
+
+    /// Write progress and results as line-delimited JSON objects
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// Execute transactions without asking for confirmation first; only asked when stdin is a terminal anyway
+    #[arg(long, short, global = true)]
+    pub yes: bool,
+
+    /// Only log warnings and errors
+    #[arg(long, short, global = true)]
+    pub quiet: bool,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Run the full challenge: mint MINTCOINs, then merge, split and call `get_flag`
+    #[command(visible_alias = "run")]
+    RunAll(RunArgs),
+    /// Run transaction 1 only: mint MINTCOINs
+    Mint(MintArgs),
+    /// Run transaction 2 only: merge MINTCOINs the sender already owns, split and call `get_flag`
+    GetFlag(GetFlagArgs),
+    /// Show the sender's IOTA gas balance and MINTCOIN holdings
+    Balance,
+    /// Check the RPC endpoint, keystore, gas coins and package before a run, one pass/fail line each
+    Doctor,
+    /// Sign a transaction written by --build-only, without network access, and write the detached signature
+    Sign(SignArgs),
+    /// Execute a transaction written by --build-only with the signature written by `sign`
+    Submit(SubmitArgs),
+}
+
+#[derive(Debug, clap::Args)]
+pub struct RunArgs {
+    #[command(flatten)]
+    pub tx: TxArgs,
+
+    /// Number of MINTCOINs minted by transaction 1 and merged by transaction 2 [default: 3]
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub mint_count: Option<u32>,
+
+    /// Exact coin value split off for `get_flag` [default: 5]
+    #[arg(long, visible_alias = "flag-amount", value_parser = clap::value_parser!(u64).range(1..))]
+    pub flag_coin_value: Option<u64>,
+
+    /// Number of flag coins to split off, calling `get_flag` once with each [default: 1]
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub flag_count: Option<u64>,
+
+    /// Seconds to wait for minted coins to be indexed before giving up
+    #[arg(long, default_value_t = DEFAULT_COIN_TIMEOUT_SECS)]
+    pub coin_timeout: u64,
+
+    /// Lookups of the minted coins before giving up on them being indexed, even within --coin-timeout
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub coin_attempts: Option<u32>,
+
+    /// Seconds the whole run may take before it is abandoned, naming the step it was stuck in
+    #[arg(long, default_value_t = DEFAULT_RUN_TIMEOUT_SECS, value_parser = clap::value_parser!(u64).range(1..))]
+    pub timeout: u64,
+
+    /// Write a JSON receipt of the run (digests, gas, flag object, timestamp) to this file
+    #[arg(long)]
+    pub receipt: Option<PathBuf>,
+
+    /// Mint and claim the flag in one transaction when `mint_coin` returns the coin; otherwise use two
+    #[arg(long)]
+    pub single_tx: bool,
+
+    /// Send the Flag to this address instead of the sender; needs a get_flag that returns the flag
+    #[arg(long, visible_alias = "transfer-flag-to", value_parser = parse_address)]
+    pub flag_recipient: Option<IotaAddress>,
+
+    /// Subscribe to mintcoin events over WebSocket before claiming the flag and print the claim's event as it
+    /// arrives
+    #[arg(long)]
+    pub watch_flag_event: bool,
+
+    /// Never mint; run transaction 2 with MINTCOINs the sender already owns, waiting for --mint-count of them
+    #[arg(long, conflicts_with = "single_tx")]
+    pub skip_tx1: bool,
+
+    /// Print the gas coin and MINTCOINs the run would use and how it would split them, then stop without
+    /// executing anything
+    #[arg(long, conflicts_with = "dry_run")]
+    pub explain: bool,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct MintArgs {
+    #[command(flatten)]
+    pub tx: TxArgs,
+
+    /// Number of MINTCOINs to mint [default: 3]
+    #[arg(long, visible_alias = "mint-count", value_parser = clap::value_parser!(u32).range(1..))]
+    pub count: Option<u32>,
+
+    /// Write the unsigned transaction to this file as BCS instead of signing and executing it
+    #[arg(long, conflicts_with = "dry_run")]
+    pub build_only: Option<PathBuf>,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct GetFlagArgs {
+    #[command(flatten)]
+    pub tx: TxArgs,
+
+    /// Number of owned MINTCOINs to merge [default: 3]
+    #[arg(long, visible_alias = "mint-count", value_parser = clap::value_parser!(u32).range(1..))]
+    pub count: Option<u32>,
+
+    /// Exact coin value split off for `get_flag` [default: 5]
+    #[arg(long, visible_alias = "flag-amount", value_parser = clap::value_parser!(u64).range(1..))]
+    pub flag_coin_value: Option<u64>,
+
+    /// Number of flag coins to split off, calling `get_flag` once with each [default: 1]
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub flag_count: Option<u64>,
+
+    /// Send the Flag to this address instead of the sender; needs a get_flag that returns the flag
+    #[arg(long, visible_alias = "transfer-flag-to", value_parser = parse_address)]
+    pub flag_recipient: Option<IotaAddress>,
+
+    /// Subscribe to mintcoin events over WebSocket before claiming the flag and print the claim's event as it
+    /// arrives
+    #[arg(long)]
+    pub watch_flag_event: bool,
+
+    /// Write the unsigned transaction to this file as BCS instead of signing and executing it
+    #[arg(long, conflicts_with = "dry_run")]
+    pub build_only: Option<PathBuf>,
+
+    /// Print the gas coin and MINTCOINs transaction 2 would use and how it would split them, then stop without
+    /// executing anything
+    #[arg(long, conflicts_with_all = ["dry_run", "build_only"])]
+    pub explain: bool,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct SignArgs {
+    /// Unsigned transaction written by --build-only
+    pub tx_file: PathBuf,
+
+    /// File to write the signature to as BCS
+    #[arg(long, short)]
+    pub out: PathBuf,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct SubmitArgs {
+    /// Unsigned transaction written by --build-only
+    pub tx_file: PathBuf,
+
+    /// Signature of it written by `sign`
+    pub sig_file: PathBuf,
+
+    /// Retries of the submission after transient RPC errors
+    #[arg(long, default_value_t = DEFAULT_MAX_ATTEMPTS - 1)]
+    pub max_retries: u32,
+}
+
+/// Options shared by every subcommand that sends transactions.
+#[derive(Debug, clap::Args)]
+pub struct TxArgs {
+    /// Faucet endpoint to request gas from when the sender has no coins, e.g. https://faucet.testnet.iota.cafe/gas;
+    /// overrides the one of --network
+    #[arg(long, value_parser = parse_http_url)]
+    pub faucet_url: Option<String>,
+
+    /// Wait for each transaction to be included in a checkpoint before continuing
+    #[arg(long)]
+    pub wait_for_finality: bool,
+
+    /// Seconds to wait for checkpoint inclusion under --wait-for-finality
+    #[arg(long, default_value_t = 60, requires = "wait_for_finality")]
+    pub finality_timeout: u64,
+
+    /// Fixed gas budget per transaction; estimated from a dry run when omitted
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub gas_budget: Option<u64>,
+
+    /// Address paying for gas with its own coins; its key must be in the keystore to co-sign every transaction
+    #[arg(long, value_parser = parse_address)]
+    pub sponsor: Option<IotaAddress>,
+
+    /// The sponsor's coin to pay gas with, instead of its largest one covering the budget
+    #[arg(long, requires = "sponsor", value_parser = parse_object_id)]
+    pub sponsor_gas_coin: Option<ObjectID>,
+
+    /// Refuse to run when the network's reference gas price is above this
+    #[arg(long)]
+    pub max_gas_price: Option<u64>,
+
+    /// Safety multiplier applied to the dry-run gas usage when estimating the budget
+    #[arg(long, default_value_t = DEFAULT_GAS_MULTIPLIER, value_parser = parse_gas_multiplier)]
+    pub gas_multiplier: f64,
+
+    /// Retries of a transaction submission after transient RPC errors
+    #[arg(long, default_value_t = DEFAULT_MAX_ATTEMPTS - 1)]
+    pub max_retries: u32,
+
+    /// Wait for local execution on the fullnode (`local`) or only for the effects certificate (`effects`)
+    #[arg(long, default_value = "local", value_parser = parse_wait_strategy)]
+    pub wait_strategy: WaitStrategy,
+
+    /// Request full transaction responses, including input objects, instead of just effects and object changes
+    #[arg(long)]
+    pub full_content: bool,
+
+    /// Simulate the transactions with a dry run instead of executing them
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Print a table of the objects each transaction created, mutated or deleted
+    #[arg(long)]
+    pub verbose_changes: bool,
+
+    /// Print the full effects of each transaction instead of only a one-line summary
+    #[arg(long)]
+    pub verbose_effects: bool,
+}
+
+impl Args {
+    /// Built-in defaults, overridden by `--config`, overridden by the options given on the command line.
+    pub fn config(&self) -> Result<ChallengeConfig, ChallengeError> {
+        let mut config = ChallengeConfig::load(self.config.as_deref())?;
+        if let Some(path) = &self.manifest {
+            let flags = DeploymentManifest {
+                package_id: self.package_id,
+                treasury_cap_id: self.treasury_cap_id,
+                shared_counter_id: self.shared_counter_id,
+            };
+            DeploymentManifest::load(path)?
+                .overridden_by(flags)
+                .apply_to(&mut config.deployment, path)?;
+        }
+        config.merge(FileConfig {
+            network: self.network,
+            rpc_url: self.rpc_urls.first().cloned(),
+            fallback_rpc_urls: (self.rpc_urls.len() > 1).then(|| self.rpc_urls[1..].to_vec()),
+            rpc_headers: (!self.rpc_headers.is_empty()).then(|| self.rpc_headers.clone()),
+            ws_url: self.ws_url.clone(),
+            request_timeout: self.request_timeout,
+            package_id: self.package_id,
+            treasury_cap_id: self.treasury_cap_id,
+            shared_counter_id: self.shared_counter_id,
+            coin_module: self.coin_module.clone(),
+            coin_struct: self.coin_struct.clone(),
+            flag_consumes_coin: self.flag_consumes_coin.then_some(true),
+            ..FileConfig::default()
+        });
+        let (mint_count, flag_coin_value, flag_count, watch_flag_event) = match &self.command {
+            Command::RunAll(run_args) => (
+                run_args.mint_count,
+                run_args.flag_coin_value,
+                run_args.flag_count,
+                run_args.watch_flag_event,
+            ),
+            Command::Mint(mint_args) => (mint_args.count, None, None, false),
+            Command::GetFlag(get_flag_args) => (
+                get_flag_args.count,
+                get_flag_args.flag_coin_value,
+                get_flag_args.flag_count,
+                get_flag_args.watch_flag_event,
+            ),
+            Command::Balance | Command::Doctor | Command::Sign(_) | Command::Submit(_) => (None, None, None, false),
+        };
+        // Only connect over WebSocket when something subscribes: the flag event watch, or run-all waiting for the
+        // effects of transaction 1 when a WebSocket endpoint was given.
+        if watch_flag_event {
+            config.ws_url.get_or_insert_with(|| ws_url_for(&config.rpc_url));
+        } else if !matches!(self.command, Command::RunAll(_)) {
+            config.ws_url = None;
+        }
+        config.merge(FileConfig {
+            mint_count,
+            flag_coin_value,
+            flag_count,
+            ..FileConfig::default()
+        });
+        Ok(config)
+    }
+
+    pub fn sender_selection(&self) -> SenderSelection {
+        match (self.sender, self.address_index) {
+            (Some(address), _) => SenderSelection::Address(address),
+            (None, Some(index)) => SenderSelection::Index(index),
+            (None, None) => SenderSelection::First,
+        }
+    }
+}
+
+fn parse_object_id(s: &str) -> Result<ObjectID, String> {
+    ObjectID::from_str(s).map_err(|e| format!("`{s}` is not a valid object id ({e}); expected a 0x-prefixed hex string"))
+}
+
+fn parse_coin_type(s: &str) -> Result<String, String> {
+    StructTag::from_str(s).map_err(|e| format!("`{s}` is not a valid coin type ({e}); expected e.g. 0x2::iota::IOTA"))?;
+    Ok(s.to_string())
+}
+
+fn parse_address(s: &str) -> Result<IotaAddress, String> {
+    IotaAddress::from_str(s).map_err(|e| format!("`{s}` is not a valid address ({e})"))
+}
+
+fn parse_wait_strategy(s: &str) -> Result<WaitStrategy, String> {
+    match s {
+        "local" => Ok(WaitStrategy::Local),
+        "effects" => Ok(WaitStrategy::Effects),
+        _ => Err(format!("unknown wait strategy `{s}`, expected local or effects")),
+    }
+}
+
+fn parse_gas_multiplier(s: &str) -> Result<f64, String> {
+    let multiplier: f64 = s.parse().map_err(|e| format!("invalid multiplier: {e}"))?;
+    if !(multiplier >= 1.0 && multiplier.is_finite()) {
+        return Err("gas multiplier must be a finite number of at least 1.0".to_string());
+    }
+    Ok(multiplier)
+}
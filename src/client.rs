@@ -0,0 +1,900 @@
+use futures::{StreamExt, stream::BoxStream};
+use iota_sdk::{
+    IotaClient, IotaClientBuilder,
+    types::{
+        Identifier,
+        base_types::{IotaAddress, ObjectID, ObjectRef},
+        digests::TransactionDigest,
+        transaction::{ObjectArg, TransactionData, TransactionDataAPI},
+    },
+    rpc_types::{
+        Coin, EventFilter, IotaTransactionBlockEffects, IotaTransactionBlockEffectsAPI, IotaTransactionBlockResponse,
+        TransactionFilter,
+    },
+};
+use move_core_types::language_storage::StructTag;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{instrument, warn};
+
+use crate::{
+    faucet, offline, Balances, ChallengeConfig, ChallengeError, CoinSelection, CoinSource, DEFAULT_FLAG_COIN_VALUE,
+    Deployment, DryRunResult, ExecuteConfig, GasConfig, Reporter, Signer, Stage, WaitStrategy, DEFAULT_MAX_ATTEMPTS,
+    retry, FailoverClient, apply_gas_budget, attach_gas, build_mint_tx, counter_value, created_object_refs, dry_run,
+    extract_flag_object, get_flag_consumes_coin, get_flag_ptb, get_flag_returns_flag, mint_coin_returns_coin,
+    pick_fresh_gas_coin, select_coins, shared_object_arg, single_tx_ptb, summarize_object_changes, treasury_cap_arg,
+    verify_package, wait_for_checkpoint, wait_for_coins,
+};
+
+/// Effects of the sender's transactions as the node processes them, from `watch_own_effects`.
+pub type EffectsStream = BoxStream<'static, Result<IotaTransactionBlockEffects, iota_sdk::error::Error>>;
+
+/// Which keystore account signs the challenge transactions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SenderSelection {
+    /// The first address in the keystore.
+    #[default]
+    First,
+    /// A specific address, which must be in the keystore.
+    Address(IotaAddress),
+    /// The address at this position in the keystore.
+    Index(usize),
+}
+
+impl SenderSelection {
+    /// The address this selection picks out of the keystore `addresses`.
+    pub fn resolve(self, addresses: Vec<IotaAddress>) -> Result<IotaAddress, ChallengeError> {
+        if addresses.is_empty() {
+            return Err(ChallengeError::NoAddresses);
+        }
+        match self {
+            SenderSelection::First => Ok(addresses[0]),
+            SenderSelection::Address(sender) if addresses.contains(&sender) => Ok(sender),
+            SenderSelection::Address(sender) => Err(ChallengeError::UnknownSender {
+                requested: sender,
+                available: addresses,
+            }),
+            SenderSelection::Index(index) => match addresses.get(index) {
+                Some(&address) => Ok(address),
+                None => Err(ChallengeError::AddressIndexOutOfRange { index, available: addresses }),
+            },
+        }
+    }
+}
+
+/// How long to wait for the `mintcoin` event of a transaction that already executed.
+pub const FLAG_EVENT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Client builder sending the configured headers with every request and giving up on each request after the
+/// configured timeout. Also connects to the WebSocket endpoint if one is configured.
+pub(crate) fn rpc_client_builder(config: &ChallengeConfig) -> IotaClientBuilder {
+    let headers: HashMap<String, String> = config
+        .rpc_headers
+        .iter()
+        .map(|header| (header.name.clone(), header.value.clone()))
+        .collect();
+    let builder = IotaClientBuilder::default()
+        .request_timeout(config.request_timeout)
+        .custom_headers(headers);
+    match &config.ws_url {
+        Some(ws_url) => builder.ws_url(ws_url),
+        None => builder,
+    }
+}
+
+/// Connects to the configured node, retrying transient failures, and gives up after `config.request_timeout`
+/// rather than hanging on a host that doesn't answer.
+pub async fn connect_rpc(config: &ChallengeConfig, reporter: &dyn Reporter) -> Result<IotaClient, ChallengeError> {
+    let rpc_url = &config.rpc_url;
+    reporter.progress(&format!("Connecting to {}", rpc_url));
+    let connect = retry(DEFAULT_MAX_ATTEMPTS, || rpc_client_builder(config).build(rpc_url));
+    let client = tokio::time::timeout(config.request_timeout, connect)
+        .await
+        .map_err(|_| ChallengeError::ConnectTimeout {
+            url: rpc_url.clone(),
+            timeout: config.request_timeout,
+        })?
+        .map_err(|source| ChallengeError::Connect {
+            url: rpc_url.clone(),
+            source,
+        })?;
+    reporter.progress(&format!("Connected to {}", rpc_url));
+    Ok(client)
+}
+
+/// Asked before a transaction is signed, once its gas budget is final; returning `false` cancels it.
+pub type Confirm = Arc<dyn Fn(Stage, &TransactionData) -> bool + Send + Sync>;
+
+/// A connected node plus the account that signs the challenge transactions.
+pub struct ChallengeClient {
+    rpc: FailoverClient,
+    signer: Signer,
+    deployment: Deployment,
+    sender_address: IotaAddress,
+    gas: GasConfig,
+    flag_coin_value: u64,
+    flag_count: u64,
+    coin_attempts: Option<u32>,
+    exec: ExecuteConfig,
+    faucet_url: Option<String>,
+    gas_coin_type: Option<String>,
+    finality_timeout: Option<Duration>,
+    verbose_changes: bool,
+    verbose_effects: bool,
+    flag_recipient: Option<IotaAddress>,
+    watch_flag_event: bool,
+    confirm: Option<Confirm>,
+    reporter: Arc<dyn Reporter>,
+}
+
+impl ChallengeClient {
+    /// Connects to the first reachable endpoint of `config`, caches the reference gas price and checks the package
+    /// with `verify_package`. Every later call fails over to the next endpoint when the current one can't be
+    /// reached.
+    ///
+    /// Transient RPC errors while connecting are retried up to `DEFAULT_MAX_ATTEMPTS` times.
+    ///
+    /// Signs as the keystore account picked by `sender`, against the deployment of `config`.
+    pub async fn connect(
+        config: &ChallengeConfig,
+        signer: Signer,
+        sender: SenderSelection,
+        reporter: Arc<dyn Reporter>,
+    ) -> Result<Self, ChallengeError> {
+        let rpc = FailoverClient::connect(config, reporter.as_ref()).await?;
+        Self::from_rpc(rpc, config.deployment.clone(), signer, sender, reporter).await
+    }
+
+    /// Like `connect`, on an `IotaClient` the caller already built, e.g. one shared with the rest of an
+    /// application. There is no endpoint to fail over to.
+    pub async fn from_client(
+        client: IotaClient,
+        deployment: Deployment,
+        signer: Signer,
+        sender: SenderSelection,
+        reporter: Arc<dyn Reporter>,
+    ) -> Result<Self, ChallengeError> {
+        Self::from_rpc(FailoverClient::from(client), deployment, signer, sender, reporter).await
+    }
+
+    async fn from_rpc(
+        rpc: FailoverClient,
+        deployment: Deployment,
+        signer: Signer,
+        sender: SenderSelection,
+        reporter: Arc<dyn Reporter>,
+    ) -> Result<Self, ChallengeError> {
+        reporter.progress(&format!("Signing with keys from {}", signer.source()));
+        let sender_address = sender.resolve(signer.addresses())?;
+        reporter.progress(&format!("Using address: {}", sender_address));
+
+        reporter.detail("Getting gas price");
+        let gas_price = retry(DEFAULT_MAX_ATTEMPTS, || rpc.get_reference_gas_price()).await?;
+        reporter.detail(&format!("Gas price: {}", gas_price));
+
+        let package_id = deployment.package_id;
+        reporter.detail(&format!("Checking package {}", package_id));
+        rpc.call(|client| async move { verify_package(&client, package_id).await })
+            .await?;
+        let mut deployment = deployment;
+        // An explicit setting, either way, wins over the deployed signature.
+        if deployment.get_flag_consumes_coin.is_none() {
+            let consumes = rpc
+                .call(|client| async move { get_flag_consumes_coin(&client, package_id).await })
+                .await?;
+            deployment.get_flag_consumes_coin = Some(consumes);
+        }
+        if deployment.get_flag_consumes_coin == Some(true) {
+            reporter.detail("get_flag consumes the flag coin; it won't be transferred back");
+        }
+
+        Ok(Self {
+            rpc,
+            signer,
+            deployment,
+            sender_address,
+            gas: GasConfig::estimated(gas_price),
+            flag_coin_value: DEFAULT_FLAG_COIN_VALUE,
+            flag_count: 1,
+            coin_attempts: None,
+            exec: ExecuteConfig::default(),
+            faucet_url: None,
+            gas_coin_type: None,
+            finality_timeout: None,
+            verbose_changes: false,
+            verbose_effects: false,
+            flag_recipient: None,
+            watch_flag_event: false,
+            confirm: None,
+            reporter,
+        })
+    }
+
+    /// Uses a fixed gas budget instead of estimating one per transaction.
+    pub fn with_gas_budget(mut self, gas_budget: Option<u64>) -> Self {
+        self.gas.budget = gas_budget;
+        self
+    }
+
+    /// Scales dry-run gas usage by `multiplier` when estimating budgets.
+    pub fn with_gas_multiplier(mut self, multiplier: f64) -> Self {
+        self.gas.multiplier = multiplier;
+        self
+    }
+
+    /// Sends the `Flag` to `recipient` instead of leaving it with the sender; needs `get_flag` to return the flag.
+    pub fn with_flag_recipient(mut self, recipient: Option<IotaAddress>) -> Self {
+        self.flag_recipient = recipient;
+        self
+    }
+
+    /// Subscribes to `mintcoin` events before claiming the flag and reports the one the claim emits as soon as it
+    /// arrives. Needs a client connected with a WebSocket URL.
+    pub fn with_flag_event_watch(mut self, watch: bool) -> Self {
+        self.watch_flag_event = watch;
+        self
+    }
+
+    /// Gives up waiting for minted coins to be indexed after `attempts` lookups, even before the timeout.
+    pub fn with_coin_attempts(mut self, attempts: Option<u32>) -> Self {
+        self.coin_attempts = attempts;
+        self
+    }
+
+    /// Asks `confirm` before signing each transaction, e.g. to prompt the user.
+    pub fn with_confirmation(mut self, confirm: Option<Confirm>) -> Self {
+        self.confirm = confirm;
+        self
+    }
+
+    /// Pays for gas with the coins of `sponsor`, whose key must also be in the keystore, instead of the sender's.
+    pub fn with_sponsor(mut self, sponsor: Option<IotaAddress>) -> Self {
+        self.gas.sponsor = sponsor;
+        self
+    }
+
+    /// Pays for gas with this coin of the sponsor only, e.g. one a gas station set aside for the participant.
+    pub fn with_sponsor_gas_coin(mut self, coin: Option<ObjectID>) -> Self {
+        self.gas.sponsor_gas_coin = coin;
+        self
+    }
+
+    /// Sets the exact coin value split off and passed to `get_flag`.
+    pub fn with_flag_coin_value(mut self, value: u64) -> Self {
+        self.flag_coin_value = value;
+        self
+    }
+
+    /// Splits off `count` flag coins and calls `get_flag` once with each, for deployments that allow repeated
+    /// claims.
+    pub fn with_flag_count(mut self, count: u64) -> Self {
+        self.flag_count = count;
+        self
+    }
+
+    /// Retries transaction submission up to `max_retries` times on transient RPC errors.
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.exec.max_attempts = max_retries.saturating_add(1);
+        self
+    }
+
+    /// Chooses whether execution waits for local execution on the fullnode or only for the effects certificate.
+    pub fn with_wait_strategy(mut self, wait: WaitStrategy) -> Self {
+        self.exec.wait = wait;
+        self
+    }
+
+    /// Requests full transaction responses, including input objects, rather than just effects and object changes.
+    pub fn with_full_content(mut self, full_content: bool) -> Self {
+        self.exec.full_content = full_content;
+        self
+    }
+
+    /// Requests funds from this faucet when the sender has no gas coins, instead of failing.
+    pub fn with_faucet_url(mut self, faucet_url: Option<String>) -> Self {
+        self.faucet_url = faucet_url;
+        self
+    }
+
+    /// Pays gas with coins of this type instead of the node's default gas coin.
+    pub fn with_gas_coin_type(mut self, gas_coin_type: Option<String>) -> Self {
+        self.gas_coin_type = gas_coin_type;
+        self
+    }
+
+    /// After each transaction, waits up to `timeout` for it to be included in a checkpoint.
+    pub fn with_finality_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.finality_timeout = timeout;
+        self
+    }
+
+    /// Reports a table of the objects each executed transaction created, mutated or deleted.
+    pub fn with_verbose_changes(mut self, verbose_changes: bool) -> Self {
+        self.verbose_changes = verbose_changes;
+        self
+    }
+
+    /// Reports the full effects of each executed or simulated transaction, not just their summary.
+    pub fn with_verbose_effects(mut self, verbose_effects: bool) -> Self {
+        self.verbose_effects = verbose_effects;
+        self
+    }
+
+    /// The endpoint currently in use.
+    pub fn client(&self) -> &IotaClient {
+        self.rpc.current()
+    }
+
+    pub fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    pub fn deployment(&self) -> &Deployment {
+        &self.deployment
+    }
+
+    pub fn sender_address(&self) -> IotaAddress {
+        self.sender_address
+    }
+
+    pub fn gas_price(&self) -> u64 {
+        self.gas.price
+    }
+
+    pub fn reporter(&self) -> &dyn Reporter {
+        self.reporter.as_ref()
+    }
+
+    /// Coins of the configured gas coin type owned by the gas payer: the sponsor if set, otherwise the sender.
+    async fn gas_coins(&self) -> Result<Vec<Coin>, ChallengeError> {
+        Ok(self.rpc.get_coins(self.gas_payer(), self.gas_coin_type.clone()).await?)
+    }
+
+    fn gas_payer(&self) -> IotaAddress {
+        self.gas.payer(self.sender_address)
+    }
+
+    fn gas_coin_type(&self) -> &str {
+        self.gas_coin_type.as_deref().unwrap_or(faucet::GAS_COIN_TYPE)
+    }
+
+    /// Gas coins of the gas payer, requesting funds from the faucet first if configured and it has none.
+    async fn funded_gas_coins(&self) -> Result<Vec<Coin>, ChallengeError> {
+        self.reporter.detail("Getting coins for gas");
+        let mut coins = self.gas_coins().await?;
+
+        if let (true, Some(faucet_url)) = (coins.is_empty(), &self.faucet_url) {
+            self.reporter
+                .progress(&format!("No gas coins found; requesting funds from {}", faucet_url));
+            faucet::request_funds(faucet_url, self.gas_payer()).await?;
+            coins = wait_for_coins(
+                &self.rpc,
+                self.gas_payer(),
+                self.gas_coin_type(),
+                1,
+                faucet::FAUCET_TIMEOUT,
+                None,
+                self.reporter.as_ref(),
+            )
+            .await?;
+            self.reporter.progress("Faucet funds arrived");
+        }
+
+        self.reporter.detail(&format!("Found {} gas coins", coins.len()));
+        Ok(coins)
+    }
+
+    /// Picks the gas payer's largest up-to-date gas coin that covers the budget, funding it from the faucet first if
+    /// configured and it has no gas coins.
+    async fn gas_coin(&self) -> Result<ObjectRef, ChallengeError> {
+        let coins = self.gas.gas_coin_candidates(self.funded_gas_coins().await?);
+        pick_fresh_gas_coin(&self.rpc, &coins, self.gas.max_budget(), &[]).await
+    }
+
+    /// Total gas balance plus the number and total balance of MINTCOINs held by the sender.
+    pub async fn balances(&self) -> Result<Balances, ChallengeError> {
+        let (gas, mint) = self
+            .rpc
+            .call(|client| async move {
+                let coin_read = client.coin_read_api();
+                let gas = coin_read
+                    .get_balance(self.sender_address, Some(self.gas_coin_type().to_string()))
+                    .await?;
+                let mint = coin_read
+                    .get_balance(self.sender_address, Some(self.deployment.mint_coin_type()))
+                    .await?;
+                Ok::<_, ChallengeError>((gas, mint))
+            })
+            .await?;
+        Ok(Balances {
+            address: self.sender_address,
+            gas_balance: gas.total_balance,
+            mint_coin_count: mint.coin_object_count,
+            mint_balance: mint.total_balance,
+        })
+    }
+
+    /// Transaction 1: mints `count` MINTCOINs and returns their object references, read from the effects rather
+    /// than by polling `get_coins`, so they are usable right away.
+    pub async fn mint_coins(&self, count: u32) -> Result<Vec<ObjectRef>, ChallengeError> {
+        let response = self.mint(count).await?;
+        created_object_refs(&response)
+    }
+
+    /// Builds the unsigned transaction 1 minting `count` MINTCOINs, paid for with a gas coin of the sender's.
+    pub async fn build_mint_tx(&self, count: u32) -> Result<TransactionData, ChallengeError> {
+        let gas_coin = self.gas_coin().await?;
+        self.rpc
+            .call(|client| async move {
+                build_mint_tx(
+                    &client,
+                    &self.deployment,
+                    self.sender_address,
+                    gas_coin,
+                    &self.gas,
+                    count,
+                    self.reporter.as_ref(),
+                )
+                .await
+            })
+            .await
+    }
+
+    /// Sets the gas budget of the unsigned `tx_data` and writes it to `path` as BCS, to be signed elsewhere.
+    pub async fn write_unsigned(
+        &self,
+        stage: Stage,
+        mut tx_data: TransactionData,
+        path: &Path,
+    ) -> Result<(), ChallengeError> {
+        apply_gas_budget(&self.rpc, &mut tx_data, &self.gas, self.reporter.as_ref()).await?;
+        offline::write_bcs(path, &tx_data)?;
+        self.reporter.progress(&format!("{} written unsigned to {}", stage, path.display()));
+        Ok(())
+    }
+
+    /// Transaction 1: mints `count` MINTCOINs to the sender and returns the full response.
+    #[instrument(name = "tx1", skip_all)]
+    pub async fn mint(&self, count: u32) -> Result<IotaTransactionBlockResponse, ChallengeError> {
+        let tx_data1 = self.build_mint_tx(count).await?;
+        let response = self.sign_and_execute(Stage::Mint, tx_data1).await?;
+        self.reporter.progress("Transaction 1 succeeded");
+        self.report_object_changes(Stage::Mint, &response);
+        self.await_finality(Stage::Mint, &response).await?;
+        Ok(response)
+    }
+
+    /// Signs and executes the built `stage` transaction with this client's gas and execution settings, once
+    /// confirmed if a confirmation is configured.
+    async fn sign_and_execute(
+        &self,
+        stage: Stage,
+        mut tx_data: TransactionData,
+    ) -> Result<IotaTransactionBlockResponse, ChallengeError> {
+        let mut gas = self.gas;
+        if let Some(confirm) = &self.confirm {
+            // Settle the budget first so the confirmation shows what will actually be spent at most.
+            apply_gas_budget(&self.rpc, &mut tx_data, &self.gas, self.reporter.as_ref()).await?;
+            if !confirm(stage, &tx_data) {
+                return Err(ChallengeError::NotConfirmed(stage));
+            }
+            gas.budget = Some(tx_data.gas_budget());
+        }
+        crate::sign_and_execute(
+            &self.rpc,
+            &self.signer,
+            tx_data,
+            stage,
+            &gas,
+            &self.exec,
+            self.reporter.as_ref(),
+        )
+        .await
+    }
+
+    /// Executes a transaction calling `get_flag`, watching for its `mintcoin` event when enabled. The subscription
+    /// starts before execution so the event can't be missed, and is dropped, which unsubscribes, once the event
+    /// arrived or `FLAG_EVENT_TIMEOUT` passed. A missing event is reported but doesn't fail the run.
+    async fn execute_claim(
+        &self,
+        stage: Stage,
+        tx_data: TransactionData,
+    ) -> Result<IotaTransactionBlockResponse, ChallengeError> {
+        if !self.watch_flag_event {
+            return self.sign_and_execute(stage, tx_data).await;
+        }
+        let filter = EventFilter::MoveEventModule {
+            package: self.deployment.package_id,
+            module: Identifier::new("mintcoin")?,
+        };
+        let events = self.rpc.current().event_api().subscribe_event(filter).await?;
+        let mut events = std::pin::pin!(events);
+        self.reporter.detail("Subscribed to mintcoin events");
+
+        let response = self.sign_and_execute(stage, tx_data).await?;
+
+        // Everyone's mintcoin events arrive here; only the one of our transaction counts.
+        let own_event = async {
+            while let Some(event) = events.next().await {
+                match event {
+                    Ok(event) if event.id.tx_digest == response.digest => return Some(event),
+                    Ok(_) => {}
+                    Err(error) => {
+                        self.reporter.detail(&format!("Event subscription failed: {}", error));
+                        return None;
+                    }
+                }
+            }
+            None
+        };
+        match tokio::time::timeout(FLAG_EVENT_TIMEOUT, own_event).await {
+            Ok(Some(event)) => self.reporter.flag_event(stage, &event),
+            _ => self.reporter.progress(&format!(
+                "No mintcoin event from {} within {}s",
+                stage.to_string().to_lowercase(),
+                FLAG_EVENT_TIMEOUT.as_secs()
+            )),
+        }
+        Ok(response)
+    }
+
+    /// Reports the object changes of `response` when `--verbose-changes` is set, and its full effects when
+    /// `--verbose-effects` is.
+    fn report_object_changes(&self, stage: Stage, response: &IotaTransactionBlockResponse) {
+        if self.verbose_changes {
+            self.reporter.object_changes(stage, &summarize_object_changes(response));
+        }
+        if let Some(effects) = &response.effects {
+            self.report_effects(stage, effects);
+        }
+    }
+
+    /// Reports the full `effects` when `--verbose-effects` is set.
+    fn report_effects(&self, stage: Stage, effects: &IotaTransactionBlockEffects) {
+        if self.verbose_effects {
+            self.reporter.effects(stage, effects);
+        }
+    }
+
+    /// Waits for checkpoint inclusion of `response` when finality waiting is enabled; `true` once it is final.
+    async fn await_finality(
+        &self,
+        stage: Stage,
+        response: &IotaTransactionBlockResponse,
+    ) -> Result<bool, ChallengeError> {
+        let Some(timeout) = self.finality_timeout else {
+            return Ok(false);
+        };
+        self.reporter.progress(&format!("Waiting for {} to be checkpointed", stage));
+        let digest = response.digest;
+        let checkpoint = self
+            .rpc
+            .call(|client| async move { wait_for_checkpoint(&client, digest, timeout).await })
+            .await?;
+        self.reporter
+            .progress(&format!("{} finalized in checkpoint {}", stage, checkpoint));
+        Ok(true)
+    }
+
+    /// Waits up to `timeout`, or for as many lookups as `with_coin_attempts` allows, until the sender holds at least
+    /// `min_count` MINTCOINs and returns them.
+    pub async fn wait_for_mint_coins(
+        &self,
+        min_count: usize,
+        timeout: Duration,
+    ) -> Result<Vec<Coin>, ChallengeError> {
+        self.reporter.progress(&format!("Waiting for {} MINTCOINs to be indexed", min_count));
+        let coin_type = self.deployment.mint_coin_type();
+        let mint_coins = wait_for_coins(
+            &self.rpc,
+            self.sender_address,
+            &coin_type,
+            min_count,
+            timeout,
+            self.coin_attempts,
+            self.reporter.as_ref(),
+        )
+        .await
+        .map_err(|e| match e {
+            ChallengeError::CoinWaitTimeout { found, attempts, .. } => ChallengeError::InsufficientMintCoins {
+                needed: min_count,
+                found,
+                attempts,
+            },
+            other => other,
+        })?;
+        self.reporter.progress(&format!("Found {} MINTCOINs", mint_coins.len()));
+
+        Ok(mint_coins)
+    }
+
+    /// Subscribes to the effects of the sender's transactions when a WebSocket endpoint is configured, so
+    /// `wait_for_mint_effects` can tell when transaction 1 was processed; `None` means waiting has to poll. Subscribe
+    /// before executing, or the effects may pass before anyone listens.
+    pub async fn watch_own_effects(&self) -> Result<Option<EffectsStream>, ChallengeError> {
+        if self.rpc.ws_url().is_none() {
+            return Ok(None);
+        }
+        let filter = TransactionFilter::FromAddress(self.sender_address);
+        let effects = self.rpc.current().event_api().subscribe_transaction(filter).await?;
+        self.reporter.detail("Subscribed to the effects of the sender's transactions");
+        Ok(Some(effects.boxed()))
+    }
+
+    /// Waits up to `timeout` for the effects of transaction 1, `digest`, to arrive on `effects`, then returns the
+    /// sender's MINTCOINs once there are at least `min_count`. Polls only for what the indexer hasn't caught up on
+    /// by then, or when the subscription ends without the effects.
+    pub async fn wait_for_mint_effects(
+        &self,
+        mut effects: EffectsStream,
+        digest: TransactionDigest,
+        min_count: usize,
+        timeout: Duration,
+    ) -> Result<Vec<Coin>, ChallengeError> {
+        self.reporter.progress("Waiting for the effects of transaction 1");
+        let started = Instant::now();
+        // Effects of any transaction of the sender's arrive here; only those of transaction 1 count.
+        let own_effects = async {
+            while let Some(update) = effects.next().await {
+                match update {
+                    Ok(update) if *update.transaction_digest() == digest => return true,
+                    Ok(_) => {}
+                    Err(error) => {
+                        self.reporter.detail(&format!("Effects subscription failed: {}", error));
+                        return false;
+                    }
+                }
+            }
+            false
+        };
+        match tokio::time::timeout(timeout, own_effects).await {
+            Ok(true) => self.reporter.detail("Effects of transaction 1 arrived"),
+            _ => self.reporter.progress("No effects of transaction 1 over WebSocket; polling for the MINTCOINs"),
+        }
+        // Unsubscribes; whatever is left to wait for is the indexer.
+        drop(effects);
+        self.wait_for_mint_coins(min_count, timeout.saturating_sub(started.elapsed()))
+            .await
+    }
+
+    /// Returns the MINTCOINs the sender currently owns, without waiting for new ones.
+    pub async fn owned_mint_coins(&self) -> Result<Vec<Coin>, ChallengeError> {
+        let coin_type = self.deployment.mint_coin_type();
+        Ok(self.rpc.get_coins(self.sender_address, Some(coin_type)).await?)
+    }
+
+    /// Reports the gas coin, the first `mint_count` owned MINTCOINs and how transaction 2 would split them,
+    /// without building or executing anything.
+    pub async fn explain_coins(&self, mint_count: u32) -> Result<CoinSelection, ChallengeError> {
+        let gas_coins = self.gas.gas_coin_candidates(self.gas_coins().await?);
+        let mint_coins = self.owned_mint_coins().await?;
+        let selection = select_coins(
+            gas_coins,
+            mint_coins,
+            mint_count as usize,
+            self.gas.max_budget(),
+            self.flag_coin_value,
+            self.flag_count,
+        )?;
+        self.reporter.coin_selection(&selection);
+        Ok(selection)
+    }
+
+    /// Simulates transaction 1 without signing it.
+    #[instrument(name = "tx1", skip_all)]
+    pub async fn dry_run_mint(&self, count: u32) -> Result<DryRunResult, ChallengeError> {
+        let tx_data1 = self.build_mint_tx(count).await?;
+        self.reporter.progress("Dry-running transaction 1");
+        let result = dry_run(&self.rpc, &tx_data1).await?;
+        self.reporter.simulated(Stage::Mint, &result);
+        self.report_effects(Stage::Mint, &result.effects);
+        Ok(result)
+    }
+
+    /// Simulates transaction 2 without signing it.
+    #[instrument(name = "tx2", skip_all)]
+    pub async fn dry_run_get_flag(&self, coins: &[Coin]) -> Result<DryRunResult, ChallengeError> {
+        let tx_data2 = self.build_get_flag_tx(coins, None).await?;
+        self.reporter.progress("Dry-running transaction 2");
+        let result = dry_run(&self.rpc, &tx_data2).await?;
+        self.reporter.simulated(Stage::GetFlag, &result);
+        self.report_effects(Stage::GetFlag, &result.effects);
+        Ok(result)
+    }
+
+    /// Transaction 2: merges the given coins, splits off the flag coin value and calls `get_flag` with it.
+    ///
+    /// Pays with `gas_coin` when given, e.g. the gas object of transaction 1 from `gas_object_ref_after`.
+    #[instrument(name = "tx2", skip_all)]
+    pub async fn get_flag(
+        &self,
+        coins: &[Coin],
+        gas_coin: Option<ObjectRef>,
+    ) -> Result<IotaTransactionBlockResponse, ChallengeError> {
+        let tx_data2 = self.build_get_flag_tx(coins, gas_coin).await?;
+        let counter_before = self.counter_for_check().await;
+        let response2 = self.execute_claim(Stage::GetFlag, tx_data2).await?;
+        self.report_object_changes(Stage::GetFlag, &response2);
+        match self.flag_object(&response2) {
+            Some((flag, flag_type)) => self
+                .reporter
+                .progress(&format!("Flag obtained: object {} of type {}", flag, flag_type)),
+            None => self.reporter.progress("No Flag object found in the object changes"),
+        }
+        self.reporter.progress("Transaction 2 succeeded");
+        let finalized = self.await_finality(Stage::GetFlag, &response2).await?;
+        self.check_counter_increased(counter_before, finalized).await;
+
+        Ok(response2)
+    }
+
+    /// Where the PTB has to send the `Flag` itself: to `--flag-recipient`, or back to the sender, whenever `get_flag`
+    /// returns it, since an unused `Flag` can't be dropped; nowhere when `get_flag` keeps the flag to itself, which is
+    /// an error if a recipient other than the sender is set.
+    async fn flag_transfer(&self) -> Result<Option<IotaAddress>, ChallengeError> {
+        let returns_flag = self
+            .rpc
+            .call(|client| async move { get_flag_returns_flag(&client, &self.deployment).await })
+            .await?;
+        match self.flag_recipient {
+            _ if returns_flag => Ok(Some(self.flag_recipient.unwrap_or(self.sender_address))),
+            Some(recipient) if recipient != self.sender_address => Err(ChallengeError::FlagNotReturned(recipient)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Whether the deployed `mint_coin` returns its coin, so `single_tx` can be used.
+    pub async fn supports_single_tx(&self) -> Result<bool, ChallengeError> {
+        self.rpc
+            .call(|client| async move { mint_coin_returns_coin(&client, &self.deployment).await })
+            .await
+    }
+
+    /// Builds the unsigned `--single-tx` transaction minting `count` MINTCOINs and claiming the flag with them.
+    pub async fn build_single_tx(&self, count: u32) -> Result<TransactionData, ChallengeError> {
+        let gas_coin = self.gas_coin().await?;
+        let treasury_cap = self
+            .rpc
+            .call(|client| async move {
+                treasury_cap_arg(&client, self.deployment.treasury_cap_id, self.sender_address, self.reporter.as_ref())
+                    .await
+            })
+            .await?;
+        let counter = self.shared_object_arg(self.deployment.shared_counter_id).await?;
+        let flag_recipient = self.flag_transfer().await?;
+        let pt = single_tx_ptb(
+            &self.deployment,
+            treasury_cap,
+            counter,
+            count,
+            self.flag_coin_value,
+            self.flag_count,
+            self.sender_address,
+            flag_recipient,
+            self.reporter.as_ref(),
+        )?;
+        Ok(self.gas.transaction(self.sender_address, gas_coin, pt))
+    }
+
+    /// Simulates the `--single-tx` transaction without signing it.
+    #[instrument(name = "tx", skip_all)]
+    pub async fn dry_run_single_tx(&self, count: u32) -> Result<DryRunResult, ChallengeError> {
+        let tx_data = self.build_single_tx(count).await?;
+        self.reporter.progress("Dry-running the single transaction");
+        let result = dry_run(&self.rpc, &tx_data).await?;
+        self.reporter.simulated(Stage::Single, &result);
+        self.report_effects(Stage::Single, &result.effects);
+        Ok(result)
+    }
+
+    /// Mints `count` MINTCOINs and claims the flag with them in one transaction, without waiting for the
+    /// minted coins to be indexed. Requires `supports_single_tx`.
+    #[instrument(name = "tx", skip_all)]
+    pub async fn single_tx(&self, count: u32) -> Result<IotaTransactionBlockResponse, ChallengeError> {
+        let tx_data = self.build_single_tx(count).await?;
+        let counter_before = self.counter_for_check().await;
+        let response = self.execute_claim(Stage::Single, tx_data).await?;
+        self.report_object_changes(Stage::Single, &response);
+        match self.flag_object(&response) {
+            Some((flag, flag_type)) => self
+                .reporter
+                .progress(&format!("Flag obtained: object {} of type {}", flag, flag_type)),
+            None => self.reporter.progress("No Flag object found in the object changes"),
+        }
+        self.reporter.progress("Single transaction succeeded");
+        let finalized = self.await_finality(Stage::Single, &response).await?;
+        self.check_counter_increased(counter_before, finalized).await;
+
+        Ok(response)
+    }
+
+    /// Current value of the shared counter passed to `get_flag`.
+    pub async fn counter_value(&self) -> Result<u64, ChallengeError> {
+        let counter_id = self.deployment.shared_counter_id;
+        self.rpc
+            .call(|client| async move { counter_value(&client, counter_id).await })
+            .await
+    }
+
+    /// Mutable shared-object argument for object `id`.
+    async fn shared_object_arg(&self, id: ObjectID) -> Result<ObjectArg, ChallengeError> {
+        self.rpc
+            .call(|client| async move { shared_object_arg(&client, id, true).await })
+            .await
+    }
+
+    /// The shared counter for `check_counter_increased`, or `None` with a warning when it can't be read or decoded,
+    /// e.g. because the deployed `Counter` has another layout. The check is a sanity check and never fails a run.
+    async fn counter_for_check(&self) -> Option<u64> {
+        self.counter_value()
+            .await
+            .inspect_err(|error| warn!("Could not read the shared counter, not checking it: {}", error))
+            .ok()
+    }
+
+    /// Reports the shared counter before and after `get_flag`, warning unless each of the `flag_count` calls
+    /// increased it. Only read again once the claim is `finalized`: before that, a node may still serve the old
+    /// version.
+    async fn check_counter_increased(&self, before: Option<u64>, finalized: bool) {
+        let Some(before) = before else {
+            return;
+        };
+        if !finalized {
+            self.reporter.progress(&format!(
+                "Counter: before={}; pass --wait-for-finality to check that get_flag increased it",
+                before
+            ));
+            return;
+        }
+        let Some(after) = self.counter_for_check().await else {
+            return;
+        };
+        self.reporter.progress(&format!("Counter: before={} after={}", before, after));
+        if after < before.saturating_add(self.flag_count) {
+            warn!(
+                "The shared counter increased by less than the {} get_flag call(s) made: before {}, after {}",
+                self.flag_count, before, after
+            );
+        }
+    }
+
+    /// The Flag object created by a `get_flag` transaction, if any.
+    pub fn flag_object(&self, response: &IotaTransactionBlockResponse) -> Option<(ObjectID, StructTag)> {
+        extract_flag_object(response, self.deployment.package_id)
+    }
+
+    /// Builds the unsigned transaction 2 from the given MINTCOINs, looking up a gas coin unless one is given.
+    pub async fn build_get_flag_tx(
+        &self,
+        coins: &[Coin],
+        gas_coin: Option<ObjectRef>,
+    ) -> Result<TransactionData, ChallengeError> {
+        if gas_coin.is_none() && self.faucet_url.is_some() {
+            // Transaction 1 may have been skipped, so this can be the first time the sender needs gas.
+            self.funded_gas_coins().await?;
+        }
+        let counter = self.shared_object_arg(self.deployment.shared_counter_id).await?;
+        let flag_recipient = self.flag_transfer().await?;
+        let pt = get_flag_ptb(
+            &self.deployment,
+            counter,
+            coins,
+            self.flag_coin_value,
+            self.flag_count,
+            self.sender_address,
+            flag_recipient,
+            self.reporter.as_ref(),
+        )?;
+        attach_gas(
+            &self.rpc,
+            self.sender_address,
+            pt,
+            gas_coin,
+            self.gas_coin_type.clone(),
+            &self.gas,
+        )
+        .await
+    }
+}
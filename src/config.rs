@@ -0,0 +1,380 @@
+use iota_sdk::types::{Identifier, base_types::ObjectID};
+use reqwest::header::{HeaderName, HeaderValue};
+use serde::Deserialize;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+use url::Url;
+
+use crate::{ChallengeError, DEFAULT_FLAG_COIN_VALUE, DEFAULT_RPC_URL, Deployment};
+
+/// Number of MINTCOINs minted unless configured otherwise.
+pub const DEFAULT_MINT_COUNT: u32 = 3;
+
+/// A public IOTA network, or a local one started with `iota start`, with its endpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    Testnet,
+    Devnet,
+    Mainnet,
+    Localnet,
+}
+
+impl Network {
+    pub fn rpc_url(self) -> &'static str {
+        match self {
+            Network::Testnet => DEFAULT_RPC_URL,
+            Network::Devnet => "https://api.devnet.iota.cafe",
+            Network::Mainnet => "https://api.mainnet.iota.cafe",
+            Network::Localnet => "http://127.0.0.1:9000",
+        }
+    }
+
+    /// Faucet endpoint; mainnet has none.
+    pub fn faucet_url(self) -> Option<&'static str> {
+        match self {
+            Network::Testnet => Some("https://faucet.testnet.iota.cafe/gas"),
+            Network::Devnet => Some("https://faucet.devnet.iota.cafe/gas"),
+            Network::Mainnet => None,
+            Network::Localnet => Some("http://127.0.0.1:9123/gas"),
+        }
+    }
+}
+
+impl FromStr for Network {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "testnet" => Ok(Network::Testnet),
+            "devnet" => Ok(Network::Devnet),
+            "mainnet" => Ok(Network::Mainnet),
+            "localnet" => Ok(Network::Localnet),
+            _ => Err(format!("unknown network `{s}`, expected testnet, devnet, mainnet or localnet")),
+        }
+    }
+}
+
+/// Seconds an RPC request, or connecting, may take unless configured otherwise.
+pub const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 30;
+
+/// Contents of a `challenge.toml`; every field is optional and falls back to the built-in default.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FileConfig {
+    /// Presets `rpc_url` and the faucet; an explicit `rpc_url` still wins.
+    pub network: Option<Network>,
+    pub rpc_url: Option<String>,
+    /// Endpoints tried in order after `rpc_url` when it can't be reached.
+    pub fallback_rpc_urls: Option<Vec<String>>,
+    pub rpc_headers: Option<Vec<RpcHeader>>,
+    pub ws_url: Option<String>,
+    /// Seconds.
+    pub request_timeout: Option<u64>,
+    pub package_id: Option<ObjectID>,
+    pub treasury_cap_id: Option<ObjectID>,
+    pub shared_counter_id: Option<ObjectID>,
+    pub coin_module: Option<String>,
+    pub coin_struct: Option<String>,
+    /// Detected from the deployed `get_flag` when not set.
+    pub flag_consumes_coin: Option<bool>,
+    pub mint_count: Option<u32>,
+    pub flag_coin_value: Option<u64>,
+    pub flag_count: Option<u64>,
+}
+
+impl FileConfig {
+    pub fn load(path: &Path) -> Result<Self, ChallengeError> {
+        let contents = std::fs::read_to_string(path).map_err(|source| ChallengeError::ConfigRead {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let config: Self = toml::from_str(&contents).map_err(|source| ChallengeError::ConfigParse {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        if config.mint_count == Some(0) {
+            return Err(ChallengeError::InvalidConfig {
+                path: path.to_path_buf(),
+                reason: "mint_count must be at least 1".to_string(),
+            });
+        }
+        if config.flag_coin_value == Some(0) {
+            return Err(ChallengeError::InvalidConfig {
+                path: path.to_path_buf(),
+                reason: "flag_coin_value must be at least 1".to_string(),
+            });
+        }
+        if config.flag_count == Some(0) {
+            return Err(ChallengeError::InvalidConfig {
+                path: path.to_path_buf(),
+                reason: "flag_count must be at least 1".to_string(),
+            });
+        }
+        if config.request_timeout == Some(0) {
+            return Err(ChallengeError::InvalidConfig {
+                path: path.to_path_buf(),
+                reason: "request_timeout must be at least 1 second".to_string(),
+            });
+        }
+        if let Some(rpc_url) = &config.rpc_url {
+            parse_http_url(rpc_url).map_err(|reason| ChallengeError::InvalidConfig {
+                path: path.to_path_buf(),
+                reason: format!("rpc_url: {reason}"),
+            })?;
+        }
+        for rpc_url in config.fallback_rpc_urls.iter().flatten() {
+            parse_http_url(rpc_url).map_err(|reason| ChallengeError::InvalidConfig {
+                path: path.to_path_buf(),
+                reason: format!("fallback_rpc_urls: {reason}"),
+            })?;
+        }
+        for (field, name) in [("coin_module", &config.coin_module), ("coin_struct", &config.coin_struct)] {
+            if let Some(name) = name {
+                parse_identifier(name).map_err(|reason| ChallengeError::InvalidConfig {
+                    path: path.to_path_buf(),
+                    reason: format!("{field}: {reason}"),
+                })?;
+            }
+        }
+        if let Some(ws_url) = &config.ws_url {
+            parse_ws_url(ws_url).map_err(|reason| ChallengeError::InvalidConfig {
+                path: path.to_path_buf(),
+                reason: format!("ws_url: {reason}"),
+            })?;
+        }
+        Ok(config)
+    }
+}
+
+/// Object IDs of a deployment as written by a deploy step, in TOML, or JSON when the file name ends in `.json`.
+/// IDs missing from the file have to be given on the command line.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeploymentManifest {
+    pub package_id: Option<ObjectID>,
+    pub treasury_cap_id: Option<ObjectID>,
+    pub shared_counter_id: Option<ObjectID>,
+}
+
+impl DeploymentManifest {
+    pub fn load(path: &Path) -> Result<Self, ChallengeError> {
+        let contents = std::fs::read_to_string(path).map_err(|source| ChallengeError::ManifestRead {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let parsed = if path.extension().is_some_and(|extension| extension == "json") {
+            serde_json::from_str(&contents).map_err(|e| e.to_string())
+        } else {
+            toml::from_str(&contents).map_err(|e| e.to_string())
+        };
+        parsed.map_err(|reason| ChallengeError::InvalidManifest {
+            path: path.to_path_buf(),
+            reason,
+        })
+    }
+
+    /// This manifest with every ID that `overrides` sets replaced, e.g. by one given on the command line.
+    pub fn overridden_by(self, overrides: DeploymentManifest) -> Self {
+        Self {
+            package_id: overrides.package_id.or(self.package_id),
+            treasury_cap_id: overrides.treasury_cap_id.or(self.treasury_cap_id),
+            shared_counter_id: overrides.shared_counter_id.or(self.shared_counter_id),
+        }
+    }
+
+    /// Sets the object IDs of `deployment` to those of this manifest, read from `path`, failing if any is missing.
+    pub fn apply_to(self, deployment: &mut Deployment, path: &Path) -> Result<(), ChallengeError> {
+        let require = |id: Option<ObjectID>, field: &str| {
+            id.ok_or_else(|| ChallengeError::InvalidManifest {
+                path: path.to_path_buf(),
+                reason: format!("no {field}; add it or pass --{}", field.replace('_', "-")),
+            })
+        };
+        deployment.package_id = require(self.package_id, "package_id")?;
+        deployment.treasury_cap_id = require(self.treasury_cap_id, "treasury_cap_id")?;
+        deployment.shared_counter_id = require(self.shared_counter_id, "shared_counter_id")?;
+        Ok(())
+    }
+}
+
+/// Settings of a challenge run after applying the config file over the built-in defaults.
+#[derive(Debug, Clone)]
+pub struct ChallengeConfig {
+    pub rpc_url: String,
+    /// Endpoints failed over to, in order, when `rpc_url` can't be reached.
+    pub fallback_rpc_urls: Vec<String>,
+    /// Faucet of the selected `--network`; `None` unless one was selected, so funds are never requested
+    /// unasked.
+    pub faucet_url: Option<String>,
+    /// Extra HTTP headers sent with every RPC request, e.g. an API key for a hosted node.
+    pub rpc_headers: Vec<RpcHeader>,
+    /// WebSocket endpoint for event subscriptions; only connected to when set.
+    pub ws_url: Option<String>,
+    /// Limit on each RPC request and on connecting.
+    pub request_timeout: Duration,
+    pub deployment: Deployment,
+    pub mint_count: u32,
+    pub flag_coin_value: u64,
+    /// Flag coins of `flag_coin_value` split off, each passed to its own `get_flag` call.
+    pub flag_count: u64,
+}
+
+impl Default for ChallengeConfig {
+    fn default() -> Self {
+        Self {
+            rpc_url: DEFAULT_RPC_URL.to_string(),
+            fallback_rpc_urls: Vec::new(),
+            faucet_url: None,
+            rpc_headers: Vec::new(),
+            ws_url: None,
+            request_timeout: Duration::from_secs(DEFAULT_REQUEST_TIMEOUT_SECS),
+            deployment: Deployment::default(),
+            mint_count: DEFAULT_MINT_COUNT,
+            flag_coin_value: DEFAULT_FLAG_COIN_VALUE,
+            flag_count: 1,
+        }
+    }
+}
+
+impl ChallengeConfig {
+    /// The defaults, overridden by the file at `path` when one is given.
+    pub fn load(path: Option<&Path>) -> Result<Self, ChallengeError> {
+        let mut config = Self::default();
+        if let Some(path) = path {
+            config.merge(FileConfig::load(path)?);
+        }
+        Ok(config)
+    }
+
+    /// `rpc_url` followed by the fallback endpoints.
+    pub fn rpc_urls(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.rpc_url.as_str()).chain(self.fallback_rpc_urls.iter().map(String::as_str))
+    }
+
+    /// Overrides every field that `file` sets.
+    pub fn merge(&mut self, file: FileConfig) {
+        if let Some(network) = file.network {
+            self.rpc_url = network.rpc_url().to_string();
+            self.faucet_url = network.faucet_url().map(str::to_string);
+        }
+        if let Some(rpc_url) = file.rpc_url {
+            self.rpc_url = rpc_url;
+        }
+        if let Some(fallback_rpc_urls) = file.fallback_rpc_urls {
+            self.fallback_rpc_urls = fallback_rpc_urls;
+        }
+        if let Some(rpc_headers) = file.rpc_headers {
+            self.rpc_headers = rpc_headers;
+        }
+        if let Some(ws_url) = file.ws_url {
+            self.ws_url = Some(ws_url);
+        }
+        if let Some(request_timeout) = file.request_timeout {
+            self.request_timeout = Duration::from_secs(request_timeout);
+        }
+        if let Some(package_id) = file.package_id {
+            self.deployment.package_id = package_id;
+        }
+        if let Some(treasury_cap_id) = file.treasury_cap_id {
+            self.deployment.treasury_cap_id = treasury_cap_id;
+        }
+        if let Some(shared_counter_id) = file.shared_counter_id {
+            self.deployment.shared_counter_id = shared_counter_id;
+        }
+        if let Some(coin_module) = file.coin_module {
+            self.deployment.coin_module = coin_module;
+        }
+        if let Some(coin_struct) = file.coin_struct {
+            self.deployment.coin_struct = coin_struct;
+        }
+        if let Some(flag_consumes_coin) = file.flag_consumes_coin {
+            self.deployment.get_flag_consumes_coin = Some(flag_consumes_coin);
+        }
+        if let Some(mint_count) = file.mint_count {
+            self.mint_count = mint_count;
+        }
+        if let Some(flag_coin_value) = file.flag_coin_value {
+            self.flag_coin_value = flag_coin_value;
+        }
+        if let Some(flag_count) = file.flag_count {
+            self.flag_count = flag_count;
+        }
+    }
+}
+
+/// Accepts only absolute `http`/`https` URLs with a host, so typos fail before we try to connect.
+pub fn parse_http_url(s: &str) -> Result<String, String> {
+    let url = Url::parse(s).map_err(|e| format!("invalid URL: {e}"))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("unsupported scheme `{}`, expected http or https", url.scheme()));
+    }
+    if url.host_str().is_none() {
+        return Err("URL has no host".to_string());
+    }
+    Ok(s.to_string())
+}
+
+/// Accepts only valid Move identifiers, such as module and struct names.
+pub fn parse_identifier(s: &str) -> Result<String, String> {
+    if !Identifier::is_valid(s) {
+        return Err(format!("`{s}` is not a valid Move identifier"));
+    }
+    Ok(s.to_string())
+}
+
+/// Accepts only absolute `ws`/`wss` URLs with a host.
+pub fn parse_ws_url(s: &str) -> Result<String, String> {
+    let url = Url::parse(s).map_err(|e| format!("invalid URL: {e}"))?;
+    if !matches!(url.scheme(), "ws" | "wss") {
+        return Err(format!("unsupported scheme `{}`, expected ws or wss", url.scheme()));
+    }
+    if url.host_str().is_none() {
+        return Err("URL has no host".to_string());
+    }
+    Ok(s.to_string())
+}
+
+/// The WebSocket endpoint a node serves next to its JSON-RPC endpoint `rpc_url`: the same URL with `ws` or `wss`
+/// instead of `http` or `https`.
+pub fn ws_url_for(rpc_url: &str) -> String {
+    match rpc_url.split_once("://") {
+        Some(("https", rest)) => format!("wss://{rest}"),
+        Some(("http", rest)) => format!("ws://{rest}"),
+        _ => rpc_url.to_string(),
+    }
+}
+
+/// An HTTP header sent with every RPC request, written `Name: Value`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct RpcHeader {
+    pub name: String,
+    pub value: String,
+}
+
+impl FromStr for RpcHeader {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, value) = s
+            .split_once(':')
+            .ok_or_else(|| format!("`{s}` is not a header; expected `Name: Value`"))?;
+        let (name, value) = (name.trim(), value.trim());
+        HeaderName::from_str(name).map_err(|e| format!("invalid header name `{name}`: {e}"))?;
+        HeaderValue::from_str(value).map_err(|e| format!("invalid value for header `{name}`: {e}"))?;
+        Ok(Self {
+            name: name.to_string(),
+            value: value.to_string(),
+        })
+    }
+}
+
+impl TryFrom<String> for RpcHeader {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
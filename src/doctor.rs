@@ -0,0 +1,87 @@
+//! The `doctor` subcommand: checks the preconditions of a run one by one.
+
+use iota_challenge3_example::{
+    ChallengeConfig, ChallengeError, CoinSource, Reporter, connect_rpc, faucet::GAS_COIN_TYPE, verify_package,
+};
+use iota_sdk::{
+    IotaClient,
+    types::base_types::{IotaAddress, ObjectID},
+};
+
+use crate::cli::Args;
+
+/// Runs every check independently, so one report shows everything that needs fixing, and fails if any did.
+pub async fn run(args: &Args, reporter: &dyn Reporter) -> Result<(), ChallengeError> {
+    let config = args.config()?;
+    let mut failures = 0;
+
+    let client = report(reporter, &mut failures, "RPC reachable", rpc(&config, reporter).await);
+    let sender = report(reporter, &mut failures, "keystore", keystore(args));
+    if let (Some(client), Some(sender)) = (&client, sender) {
+        let gas_coin_type = args.gas_coin_type.as_deref().unwrap_or(GAS_COIN_TYPE);
+        report(reporter, &mut failures, "gas coins", gas(client, sender, gas_coin_type).await);
+    }
+    if let Some(client) = &client {
+        let package_id = config.deployment.package_id;
+        report(reporter, &mut failures, "package", package(client, package_id).await);
+    }
+
+    match failures {
+        0 => Ok(()),
+        failures => Err(ChallengeError::DoctorFailed(failures)),
+    }
+}
+
+/// Reports the outcome of one check, counting failures, and passes on what it produced.
+fn report<T>(
+    reporter: &dyn Reporter,
+    failures: &mut usize,
+    name: &str,
+    result: Result<(T, String), ChallengeError>,
+) -> Option<T> {
+    match result {
+        Ok((value, detail)) => {
+            reporter.check(name, Ok(&detail));
+            Some(value)
+        }
+        Err(error) => {
+            reporter.check(name, Err(&error));
+            *failures += 1;
+            None
+        }
+    }
+}
+
+/// The endpoint answers `get_reference_gas_price`.
+async fn rpc(config: &ChallengeConfig, reporter: &dyn Reporter) -> Result<(IotaClient, String), ChallengeError> {
+    let client = connect_rpc(config, reporter).await?;
+    let gas_price = client.read_api().get_reference_gas_price().await?;
+    let detail = format!("{} answers, reference gas price {}", config.rpc_url, gas_price);
+    Ok((client, detail))
+}
+
+/// The keystore loads and holds the selected sender.
+fn keystore(args: &Args) -> Result<(IotaAddress, String), ChallengeError> {
+    let signer = crate::signer(args)?;
+    let addresses = signer.addresses();
+    let count = addresses.len();
+    let sender = args.sender_selection().resolve(addresses)?;
+    let detail = format!("{} addresses from {}, signing as {}", count, signer.source(), sender);
+    Ok((sender, detail))
+}
+
+/// The sender owns at least one coin to pay gas with.
+async fn gas(client: &IotaClient, sender: IotaAddress, gas_coin_type: &str) -> Result<((), String), ChallengeError> {
+    let coins = client.get_coins(sender, Some(gas_coin_type.to_string())).await?;
+    if coins.is_empty() {
+        return Err(ChallengeError::NoGasCoin);
+    }
+    let total: u128 = coins.iter().map(|coin| coin.balance as u128).sum();
+    Ok(((), format!("{} coins of {} holding {} in total", coins.len(), gas_coin_type, total)))
+}
+
+/// The package ID resolves to a Move package with the `mintcoin` functions the flow calls.
+async fn package(client: &IotaClient, package_id: ObjectID) -> Result<((), String), ChallengeError> {
+    verify_package(client, package_id).await?;
+    Ok(((), format!("{} exposes mintcoin::mint_coin and mintcoin::get_flag", package_id)))
+}
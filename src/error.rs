@@ -0,0 +1,258 @@
+use iota_sdk::types::{
+    base_types::{IotaAddress, ObjectID},
+    crypto::SignatureScheme,
+    digests::TransactionDigest,
+};
+use std::path::PathBuf;
+use std::time::Duration;
+use thiserror::Error;
+
+use crate::{MoveAbort, Stage, runner::Step};
+
+/// Everything that can go wrong while solving the challenge.
+#[derive(Debug, Error)]
+pub enum ChallengeError {
+    #[error("failed to connect to {url}")]
+    Connect {
+        url: String,
+        #[source]
+        source: iota_sdk::error::Error,
+    },
+
+    #[error("no answer from {url} within {}s; check the node is up, or raise --request-timeout", .timeout.as_secs())]
+    ConnectTimeout { url: String, timeout: Duration },
+
+    #[error(
+        "keystore {} does not exist; run `iota client` once to generate one, or point --keystore-path at an existing keystore",
+        .path.display()
+    )]
+    KeystoreNotFound { path: PathBuf },
+
+    #[error("failed to load keystore")]
+    Keystore(#[source] anyhow::Error),
+
+    #[error("failed to read config file {}", .path.display())]
+    ConfigRead { path: PathBuf, source: std::io::Error },
+
+    #[error("invalid config file {}", .path.display())]
+    ConfigParse { path: PathBuf, source: toml::de::Error },
+
+    #[error("invalid config file {}: {reason}", .path.display())]
+    InvalidConfig { path: PathBuf, reason: String },
+
+    #[error("failed to read deployment manifest {}", .path.display())]
+    ManifestRead { path: PathBuf, source: std::io::Error },
+
+    #[error("invalid deployment manifest {}: {reason}", .path.display())]
+    InvalidManifest { path: PathBuf, reason: String },
+
+    #[error("IOTA_PRIVATE_KEY is neither a bech32 nor a base64 private key")]
+    InvalidPrivateKey,
+
+    #[error("failed to write receipt {}", .path.display())]
+    ReceiptWrite { path: PathBuf, source: std::io::Error },
+
+    #[error("failed to write {}", .path.display())]
+    FileWrite { path: PathBuf, source: std::io::Error },
+
+    #[error("failed to read {}", .path.display())]
+    FileRead { path: PathBuf, source: std::io::Error },
+
+    #[error("could not determine the home directory")]
+    HomeDirNotFound,
+
+    #[error("no addresses in keystore")]
+    NoAddresses,
+
+    #[error("address {requested} is not in the keystore; available: {}", list_addresses(.available))]
+    UnknownSender {
+        requested: IotaAddress,
+        available: Vec<IotaAddress>,
+    },
+
+    #[error("sponsor {sponsor} is not in the keystore and cannot co-sign; available: {}", list_addresses(.available))]
+    UnknownSponsor {
+        sponsor: IotaAddress,
+        available: Vec<IotaAddress>,
+    },
+
+    #[error("no address at index {index} in the keystore; available: {}", list_addresses(.available))]
+    AddressIndexOutOfRange {
+        index: usize,
+        available: Vec<IotaAddress>,
+    },
+
+    #[error("at least one coin is needed to merge")]
+    NoCoinsToMerge,
+
+    #[error("no coins found for gas; pass --faucet-url to request funds automatically")]
+    NoGasCoin,
+
+    #[error("gas object {0} is no longer owned by an address and cannot pay for the next transaction")]
+    GasObjectNotOwned(ObjectID),
+
+    #[error("faucet request to {url} failed")]
+    Faucet { url: String, source: reqwest::Error },
+
+    #[error(
+        "no single gas coin covers the budget of {budget}: largest coin has {largest} (short by {}), {total} available in total; merge your coins or request funds from the faucet",
+        .budget - .largest
+    )]
+    InsufficientGas { budget: u64, largest: u64, total: u128 },
+
+    #[error(
+        "gas coins {} changed since they were indexed and no other coin covers the budget; retry once the fullnode catches up",
+        list_object_ids(.0)
+    )]
+    StaleGasCoins(Vec<ObjectID>),
+
+    #[error("reference gas price {reference} is above --max-gas-price {cap}")]
+    GasPriceTooHigh { reference: u64, cap: u64 },
+
+    #[error("not enough MINTCOINs: needed {needed}, found {found} (lookups: {attempts})")]
+    InsufficientMintCoins { needed: usize, found: usize, attempts: u32 },
+
+    #[error(
+        "MINTCOINs hold {available} in total, not enough to split off {needed}; mint more coins or lower --flag-amount"
+    )]
+    InsufficientMintBalance { needed: u64, available: u64 },
+
+    #[error("timed out waiting for coins of {coin_type}, found {found} (lookups: {attempts})")]
+    CoinWaitTimeout { coin_type: String, found: usize, attempts: u32 },
+
+    #[error("transaction {0} was not included in a checkpoint before the timeout")]
+    FinalityTimeout(TransactionDigest),
+
+    #[error("object {0} not found")]
+    ObjectNotFound(ObjectID),
+
+    #[error("{0} is not a Move package; check --package-id")]
+    NotAPackage(ObjectID),
+
+    #[error("package {package} has no {module} module; check --package-id")]
+    MissingModule { package: ObjectID, module: &'static str },
+
+    #[error("package {package} has no {module}::{function} function; check --package-id")]
+    MissingFunction {
+        package: ObjectID,
+        module: &'static str,
+        function: &'static str,
+    },
+
+    #[error("object {0} is not a shared object")]
+    ObjectNotShared(ObjectID),
+
+    #[error("transaction {digest} failed: {}", failure(.abort, .error))]
+    ExecutionFailed {
+        digest: TransactionDigest,
+        /// Where and with which code Move aborted, when the failure was a `MoveAbort`.
+        abort: Option<MoveAbort>,
+        error: String,
+    },
+
+    #[error("{} was not confirmed and has not been executed", .0.to_string().to_lowercase())]
+    NotConfirmed(Stage),
+
+    #[error("timed out after {}s during {step}", .timeout.as_secs())]
+    Timeout { step: Step, timeout: Duration },
+
+    #[error("interrupted; {}", list_completed(.completed))]
+    Interrupted { completed: Vec<(Stage, TransactionDigest)> },
+
+    #[error("{0} doctor check(s) failed")]
+    DoctorFailed(usize),
+
+    #[error("cannot send the flag to {0}: the deployed get_flag transfers it to the sender instead of returning it")]
+    FlagNotReturned(IotaAddress),
+
+    #[error("transaction {0} returned no effects")]
+    MissingEffects(TransactionDigest),
+
+    #[error("{0} is watch-only: no key to sign with; sign the transaction on the machine holding its key")]
+    WatchOnly(IotaAddress),
+
+    #[error("failed to sign transaction: {0}")]
+    Signing(String),
+
+    #[error("keys of scheme {0} cannot sign transactions; use an ed25519, secp256k1 or secp256r1 key")]
+    UnsupportedScheme(SignatureScheme),
+
+    #[error(transparent)]
+    Rpc(#[from] iota_sdk::error::Error),
+
+    #[error(transparent)]
+    Bcs(#[from] bcs::Error),
+
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl ChallengeError {
+    /// Process exit code for this error, so scripts can tell failures apart: 2 for configuration and usage
+    /// (matching clap's own usage errors), 3 for the network, 4 for a Move abort, 1 for anything else.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            ChallengeError::KeystoreNotFound { .. }
+            | ChallengeError::Keystore(_)
+            | ChallengeError::ConfigRead { .. }
+            | ChallengeError::ConfigParse { .. }
+            | ChallengeError::InvalidConfig { .. }
+            | ChallengeError::ManifestRead { .. }
+            | ChallengeError::InvalidManifest { .. }
+            | ChallengeError::InvalidPrivateKey
+            | ChallengeError::HomeDirNotFound
+            | ChallengeError::NoAddresses
+            | ChallengeError::UnknownSender { .. }
+            | ChallengeError::UnknownSponsor { .. }
+            | ChallengeError::AddressIndexOutOfRange { .. }
+            | ChallengeError::NotAPackage(_)
+            | ChallengeError::MissingModule { .. }
+            | ChallengeError::MissingFunction { .. }
+            | ChallengeError::ObjectNotShared(_)
+            | ChallengeError::FlagNotReturned(_)
+            | ChallengeError::WatchOnly(_)
+            | ChallengeError::UnsupportedScheme(_) => 2,
+            ChallengeError::Connect { .. }
+            | ChallengeError::ConnectTimeout { .. }
+            | ChallengeError::Faucet { .. }
+            | ChallengeError::FinalityTimeout(_)
+            | ChallengeError::CoinWaitTimeout { .. }
+            | ChallengeError::Timeout { .. }
+            | ChallengeError::Rpc(_) => 3,
+            ChallengeError::ExecutionFailed { abort: Some(_), .. } => 4,
+            _ => 1,
+        }
+    }
+}
+
+fn list_addresses(addresses: &[IotaAddress]) -> String {
+    addresses
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn list_object_ids(ids: &[ObjectID]) -> String {
+    ids.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+}
+
+fn list_completed(completed: &[(Stage, TransactionDigest)]) -> String {
+    if completed.is_empty() {
+        return "no transaction was executed".to_string();
+    }
+    let executed = completed
+        .iter()
+        .map(|(stage, digest)| format!("{} {}", stage, digest))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("already executed: {}", executed)
+}
+
+/// The parsed abort when there is one, which is much shorter than the raw execution error.
+fn failure(abort: &Option<MoveAbort>, error: &str) -> String {
+    match abort {
+        Some(abort) => abort.to_string(),
+        None => error.to_string(),
+    }
+}
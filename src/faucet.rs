@@ -0,0 +1,27 @@
+use iota_sdk::types::base_types::IotaAddress;
+use serde_json::json;
+use std::time::Duration;
+
+use crate::ChallengeError;
+
+/// Coin type the faucet pays out and gas is paid in by default.
+pub const GAS_COIN_TYPE: &str = "0x2::iota::IOTA";
+
+/// How long to wait for faucet coins to be indexed after a successful request.
+pub const FAUCET_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Asks the faucet at `faucet_url` (e.g. `https://faucet.testnet.iota.cafe/gas`) to fund `recipient`.
+pub async fn request_funds(faucet_url: &str, recipient: IotaAddress) -> Result<(), ChallengeError> {
+    let faucet_error = |source| ChallengeError::Faucet {
+        url: faucet_url.to_string(),
+        source,
+    };
+    reqwest::Client::new()
+        .post(faucet_url)
+        .json(&json!({ "FixedAmountRequest": { "recipient": recipient.to_string() } }))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(faucet_error)?;
+    Ok(())
+}
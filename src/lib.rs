@@ -0,0 +1,1280 @@
+use iota_sdk::{
+    IotaClient,
+    types::{
+        base_types::{IotaAddress, ObjectID, ObjectRef, ObjectType, SequenceNumber},
+        messages_checkpoint::CheckpointSequenceNumber,
+        digests::TransactionDigest,
+        gas::GasCostSummary,
+        object::Owner,
+        programmable_transaction_builder::ProgrammableTransactionBuilder,
+        transaction::{
+            Argument, Command, Transaction, TransactionData, TransactionDataAPI, CallArg, ObjectArg,
+            ProgrammableMoveCall, ProgrammableTransaction,
+        },
+        Identifier, IOTA_FRAMEWORK_PACKAGE_ID,
+    },
+    rpc_types::{
+        Coin, IotaExecutionStatus, IotaMoveNormalizedType, ObjectChange, IotaObjectDataOptions, IotaRawData,
+        IotaTransactionBlockEffects, IotaTransactionBlockEffectsAPI, IotaTransactionBlockResponse,
+        IotaTransactionBlockResponseOptions,
+    },
+};
+use move_core_types::{
+    account_address::AccountAddress,
+    identifier::Identifier as MoveIdentifier,
+    language_storage::{StructTag, TypeTag},
+};
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+pub mod client;
+pub mod config;
+pub mod error;
+pub mod faucet;
+pub mod offline;
+pub mod report;
+pub mod retry;
+pub mod rpc;
+pub mod runner;
+pub mod signer;
+
+pub use client::{ChallengeClient, Confirm, EffectsStream, SenderSelection, connect_rpc};
+pub use config::{ChallengeConfig, DEFAULT_MINT_COUNT, DEFAULT_REQUEST_TIMEOUT_SECS, FileConfig, Network, RpcHeader};
+pub use error::ChallengeError;
+pub use report::{HumanReporter, JsonReporter, Reporter, Stage};
+pub use rpc::{CoinSource, FailoverClient, TxExecutor};
+pub use runner::{ChallengeOutcome, ChallengeRunner, Step, run_challenge};
+pub use signer::Signer;
+pub use retry::{DEFAULT_MAX_ATTEMPTS, WaitStrategy, execute_with_retry, retry};
+
+pub const DEFAULT_RPC_URL: &str = "https://api.testnet.iota.cafe";
+
+/// Gas budget the transactions are built and dry-run with before an estimate replaces it.
+pub const GAS_BUDGET: u64 = 50_000_000;
+
+/// Default headroom applied on top of the dry-run gas usage when estimating a budget.
+pub const DEFAULT_GAS_MULTIPLIER: f64 = 1.2;
+
+/// Value `get_flag` expects the coin it is given to hold.
+pub const DEFAULT_FLAG_COIN_VALUE: u64 = 5;
+
+pub const PACKAGE_ID: &str = "0xc6f00a2b5ec2d161442b305dcb307ba914e20c5268ec931bd14d7ea3454b262b";
+pub const TREASURY_CAP_ID: &str = "0x11d7aacb27eb65063dbb6ce0fa07f7807316c5e77763c6f2356d1bd3a34a2741";
+pub const SHARED_COUNTER_ID: &str = "0xc3716689fa16bd8d8bf33ce1036b00740c8818ab9826dba846ef736501fd34b7";
+
+/// Module and struct of the challenge coin type, `<package>::mintcoin::MINTCOIN`, unless a fork renamed them.
+pub const DEFAULT_COIN_MODULE: &str = "mintcoin";
+pub const DEFAULT_COIN_STRUCT: &str = "MINTCOIN";
+
+/// Gas price, how the budget of each transaction is chosen and who pays for it.
+#[derive(Debug, Clone, Copy)]
+pub struct GasConfig {
+    pub price: u64,
+    /// Fixed budget; when `None` the budget is estimated from a dry run.
+    pub budget: Option<u64>,
+    pub multiplier: f64,
+    /// Address paying for gas with its own coins instead of the sender; it signs every transaction too.
+    pub sponsor: Option<IotaAddress>,
+    /// The sponsor's coin that pays for gas, instead of its largest one covering the budget.
+    pub sponsor_gas_coin: Option<ObjectID>,
+}
+
+impl GasConfig {
+    /// Estimates every budget with the default multiplier.
+    pub fn estimated(price: u64) -> Self {
+        Self {
+            price,
+            budget: None,
+            multiplier: DEFAULT_GAS_MULTIPLIER,
+            sponsor: None,
+            sponsor_gas_coin: None,
+        }
+    }
+
+    /// The payer's coins gas may be picked from: only `sponsor_gas_coin` when one is set, otherwise all of them.
+    pub fn gas_coin_candidates(&self, mut coins: Vec<Coin>) -> Vec<Coin> {
+        if let Some(id) = self.sponsor_gas_coin {
+            coins.retain(|coin| coin.coin_object_id == id);
+        }
+        coins
+    }
+
+    /// Whose gas coins pay for transactions sent by `sender`.
+    pub fn payer(&self, sender: IotaAddress) -> IotaAddress {
+        self.sponsor.unwrap_or(sender)
+    }
+
+    /// Transaction data for `pt` sent by `sender` and paid for with `gas_coin`, which belongs to the sponsor if
+    /// there is one.
+    pub fn transaction(&self, sender: IotaAddress, gas_coin: ObjectRef, pt: ProgrammableTransaction) -> TransactionData {
+        match self.sponsor {
+            None => TransactionData::new_programmable(sender, vec![gas_coin], pt, self.max_budget(), self.price),
+            Some(sponsor) => TransactionData::new_programmable_allow_sponsor(
+                sender,
+                vec![gas_coin],
+                pt,
+                self.max_budget(),
+                self.price,
+                sponsor,
+            ),
+        }
+    }
+
+    /// Budget the gas coin has to cover: the fixed budget if set, otherwise the estimation ceiling.
+    pub fn max_budget(&self) -> u64 {
+        self.budget.unwrap_or(GAS_BUDGET)
+    }
+}
+
+/// How signed transactions are submitted.
+#[derive(Debug, Clone, Copy)]
+pub struct ExecuteConfig {
+    /// Attempts per submission; transient RPC errors are retried until this is reached.
+    pub max_attempts: u32,
+    /// Request the full response, including input objects, instead of just effects and object changes.
+    pub full_content: bool,
+    pub wait: WaitStrategy,
+}
+
+impl Default for ExecuteConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            full_content: false,
+            wait: WaitStrategy::default(),
+        }
+    }
+}
+
+impl ExecuteConfig {
+    pub fn response_options(&self) -> IotaTransactionBlockResponseOptions {
+        response_options(self.full_content)
+    }
+}
+
+/// Effects and object changes only, which is all the flow reads, unless `full_content` is set.
+pub fn response_options(full_content: bool) -> IotaTransactionBlockResponseOptions {
+    if full_content {
+        IotaTransactionBlockResponseOptions::full_content()
+    } else {
+        IotaTransactionBlockResponseOptions::new()
+            .with_effects()
+            .with_object_changes()
+    }
+}
+
+/// Object IDs of one deployment of the challenge contract, and where in its package the coin type is defined.
+#[derive(Debug, Clone)]
+pub struct Deployment {
+    pub package_id: ObjectID,
+    pub treasury_cap_id: ObjectID,
+    pub shared_counter_id: ObjectID,
+    pub coin_module: String,
+    pub coin_struct: String,
+    /// Whether `get_flag` takes the flag coin by value, leaving nothing to transfer back after the call. `None`
+    /// until it's configured or detected on connect; treated as `false` when a PTB is built without knowing.
+    pub get_flag_consumes_coin: Option<bool>,
+}
+
+impl Default for Deployment {
+    fn default() -> Self {
+        Self {
+            package_id: ObjectID::from_str(PACKAGE_ID).expect("PACKAGE_ID is a valid object id"),
+            treasury_cap_id: ObjectID::from_str(TREASURY_CAP_ID).expect("TREASURY_CAP_ID is a valid object id"),
+            shared_counter_id: ObjectID::from_str(SHARED_COUNTER_ID).expect("SHARED_COUNTER_ID is a valid object id"),
+            coin_module: DEFAULT_COIN_MODULE.to_string(),
+            coin_struct: DEFAULT_COIN_STRUCT.to_string(),
+            get_flag_consumes_coin: None,
+        }
+    }
+}
+
+impl Deployment {
+    /// Fully qualified coin type, `<package>::mintcoin::MINTCOIN` by default.
+    pub fn mint_coin_type(&self) -> String {
+        format!("{}::{}::{}", self.package_id, self.coin_module, self.coin_struct)
+    }
+}
+
+/// Coin holdings of the sender, as reported by the `balance` subcommand.
+#[derive(Debug, Clone, Serialize)]
+pub struct Balances {
+    pub address: IotaAddress,
+    pub gas_balance: u128,
+    pub mint_coin_count: usize,
+    pub mint_balance: u128,
+}
+
+/// Looks up the `initial_shared_version` of a shared object.
+pub async fn shared_version(client: &IotaClient, id: ObjectID) -> Result<SequenceNumber, ChallengeError> {
+    let response = client
+        .read_api()
+        .get_object_with_options(id, IotaObjectDataOptions::new().with_owner())
+        .await?;
+    let owner = response
+        .data
+        .and_then(|data| data.owner)
+        .ok_or(ChallengeError::ObjectNotFound(id))?;
+
+    match owner {
+        Owner::Shared { initial_shared_version } => Ok(initial_shared_version),
+        _ => Err(ChallengeError::ObjectNotShared(id)),
+    }
+}
+
+/// On-chain layout of the shared `Counter` passed to `get_flag`, as far as it is read: fields after `value` are
+/// ignored.
+#[derive(Deserialize)]
+struct Counter {
+    #[allow(dead_code)]
+    id: ObjectID,
+    value: u64,
+}
+
+/// BCS size of `Counter`: a 32-byte ID and a `u64`.
+const COUNTER_BCS_LEN: usize = 40;
+
+/// Decodes the BCS contents of the shared `Counter` into its value, ignoring any fields that follow it.
+pub fn decode_counter(bcs_bytes: &[u8]) -> Result<u64, ChallengeError> {
+    let known = &bcs_bytes[..bcs_bytes.len().min(COUNTER_BCS_LEN)];
+    Ok(bcs::from_bytes::<Counter>(known)?.value)
+}
+
+/// Current value of the shared `Counter` `id`, read from its BCS contents.
+pub async fn counter_value(client: &IotaClient, id: ObjectID) -> Result<u64, ChallengeError> {
+    let response = client
+        .read_api()
+        .get_object_with_options(id, IotaObjectDataOptions::new().with_bcs())
+        .await?;
+    match response.data.and_then(|data| data.bcs) {
+        Some(IotaRawData::MoveObject(object)) => decode_counter(&object.bcs_bytes),
+        _ => Err(ChallengeError::ObjectNotFound(id)),
+    }
+}
+
+/// Builds a shared-object input using the object's real `initial_shared_version`.
+pub async fn shared_object_arg(
+    client: &IotaClient,
+    id: ObjectID,
+    mutable: bool,
+) -> Result<ObjectArg, ChallengeError> {
+    Ok(ObjectArg::SharedObject {
+        id,
+        initial_shared_version: shared_version(client, id).await?,
+        mutable,
+    })
+}
+
+/// Input for the treasury cap `mint_coin` takes, checked before transaction 1 is built so a sender without mint
+/// authority hears about it up front rather than from an abort deep in execution. A shared cap, or one the sender
+/// owns, is used as such; a cap anyone else owns is still passed, with a warning that minting will likely fail.
+pub async fn treasury_cap_arg(
+    client: &IotaClient,
+    id: ObjectID,
+    sender: IotaAddress,
+    reporter: &dyn Reporter,
+) -> Result<ObjectArg, ChallengeError> {
+    let data = client
+        .read_api()
+        .get_object_with_options(id, IotaObjectDataOptions::new().with_owner())
+        .await?
+        .data
+        .ok_or(ChallengeError::ObjectNotFound(id))?;
+    let owner = data.owner.ok_or(ChallengeError::ObjectNotFound(id))?;
+    reporter.detail(&format!("Treasury cap {} is owned by {}", id, owner));
+
+    match owner {
+        Owner::Shared { initial_shared_version } => Ok(ObjectArg::SharedObject {
+            id,
+            initial_shared_version,
+            mutable: true,
+        }),
+        Owner::AddressOwner(address) if address == sender => Ok(ObjectArg::ImmOrOwnedObject(data.object_ref())),
+        _ => {
+            warn!(
+                "Treasury cap {} is owned by {}, not shared or owned by the sender {}; minting will likely fail",
+                id, owner, sender
+            );
+            Ok(ObjectArg::ImmOrOwnedObject(data.object_ref()))
+        }
+    }
+}
+
+/// How often `wait_for_coins` re-queries the indexer.
+const COIN_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Polls `get_coins` every 500ms until `owner` holds at least `min_count` coins of `coin_type`, reporting the count
+/// found after every poll. Finding fewer is the indexer lagging behind execution and is retried until `timeout`, or
+/// for at most `max_attempts` lookups; an RPC error ends the wait at once.
+pub async fn wait_for_coins(
+    client: &impl CoinSource,
+    owner: IotaAddress,
+    coin_type: &str,
+    min_count: usize,
+    timeout: Duration,
+    max_attempts: Option<u32>,
+    reporter: &dyn Reporter,
+) -> Result<Vec<Coin>, ChallengeError> {
+    // A timeout too large to represent means waiting without a deadline.
+    let deadline = Instant::now().checked_add(timeout);
+    let mut attempts = 0;
+    let result = loop {
+        attempts += 1;
+        let coins = match client.get_coins(owner, Some(coin_type.to_string())).await {
+            Ok(coins) => coins,
+            Err(error) => break Err(error.into()),
+        };
+        reporter.waiting(&format!("Waiting for coins to be indexed: {}/{} found", coins.len(), min_count));
+        if coins.len() >= min_count {
+            break Ok(coins);
+        }
+        let exhausted = max_attempts.is_some_and(|max_attempts| attempts >= max_attempts);
+        if exhausted || deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            break Err(ChallengeError::CoinWaitTimeout {
+                coin_type: coin_type.to_string(),
+                found: coins.len(),
+                attempts,
+            });
+        }
+        tokio::time::sleep(COIN_POLL_INTERVAL).await;
+    };
+    reporter.waited();
+    result
+}
+
+/// Polls until the transaction `digest` is included in a checkpoint, i.e. final network-wide, and returns it.
+pub async fn wait_for_checkpoint(
+    client: &IotaClient,
+    digest: TransactionDigest,
+    timeout: Duration,
+) -> Result<CheckpointSequenceNumber, ChallengeError> {
+    // A timeout too large to represent means waiting without a deadline.
+    let deadline = Instant::now().checked_add(timeout);
+    loop {
+        // A node that hasn't seen the transaction yet reports an error; treat that as "not yet".
+        let checkpoint = client
+            .read_api()
+            .get_transaction_with_options(digest, IotaTransactionBlockResponseOptions::new())
+            .await
+            .ok()
+            .and_then(|response| response.checkpoint);
+        if let Some(checkpoint) = checkpoint {
+            return Ok(checkpoint);
+        }
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return Err(ChallengeError::FinalityTimeout(digest));
+        }
+        tokio::time::sleep(COIN_POLL_INTERVAL).await;
+    }
+}
+
+/// Picks the largest coin that covers `budget`, skipping any coin listed in `exclude`.
+pub fn pick_gas_coin<'a>(
+    coins: &'a [Coin],
+    budget: u64,
+    exclude: &[ObjectID],
+) -> Result<&'a Coin, ChallengeError> {
+    let candidates: Vec<&Coin> = coins
+        .iter()
+        .filter(|coin| !exclude.contains(&coin.coin_object_id))
+        .collect();
+    if candidates.is_empty() {
+        return Err(ChallengeError::NoGasCoin);
+    }
+    candidates
+        .iter()
+        .copied()
+        .filter(|coin| coin.balance >= budget)
+        .max_by_key(|coin| coin.balance)
+        .ok_or_else(|| ChallengeError::InsufficientGas {
+            budget,
+            largest: candidates.iter().map(|coin| coin.balance).max().unwrap_or(0),
+            total: candidates.iter().map(|coin| coin.balance as u128).sum(),
+        })
+}
+
+/// Like `pick_gas_coin`, but checks the picked coin against its live version: a coin whose indexed version
+/// is behind, e.g. because an earlier transaction paid with it moments ago, would fail with an object version
+/// mismatch. Stale coins are skipped in favour of the next best one.
+pub async fn pick_fresh_gas_coin(
+    coin_source: &impl CoinSource,
+    coins: &[Coin],
+    budget: u64,
+    exclude: &[ObjectID],
+) -> Result<ObjectRef, ChallengeError> {
+    let mut skipped = exclude.to_vec();
+    let mut stale = Vec::new();
+    loop {
+        let coin = match pick_gas_coin(coins, budget, &skipped) {
+            Ok(coin) => coin,
+            Err(_) if !stale.is_empty() => return Err(ChallengeError::StaleGasCoins(stale)),
+            Err(error) => return Err(error),
+        };
+        let indexed = coin.object_ref();
+        let latest = coin_source.latest_object_ref(indexed.0).await?;
+        if latest.is_some_and(|(_, version, _)| version == indexed.1) {
+            return Ok(indexed);
+        }
+        stale.push(indexed.0);
+        skipped.push(indexed.0);
+    }
+}
+
+/// The first `count` of `coins`: the MINTCOINs transaction 2 merges.
+pub fn select_mint_coins(mut coins: Vec<Coin>, count: usize) -> Result<Vec<Coin>, ChallengeError> {
+    if coins.len() < count {
+        return Err(ChallengeError::InsufficientMintCoins {
+            needed: count,
+            found: coins.len(),
+            attempts: 1,
+        });
+    }
+    coins.truncate(count);
+    Ok(coins)
+}
+
+/// How transaction 2 divides the merged MINTCOINs between `get_flag` and the sender.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SplitPlan {
+    pub merged_balance: u64,
+    /// Value of each coin split off and passed to `get_flag`.
+    pub flag_coin_value: u64,
+    /// Number of flag coins split off, one per `get_flag` call.
+    pub flag_count: u64,
+}
+
+impl SplitPlan {
+    /// Fails when `coins` together hold less than `flag_count` coins of `flag_coin_value`.
+    pub fn new(coins: &[Coin], flag_coin_value: u64, flag_count: u64) -> Result<Self, ChallengeError> {
+        let merged_balance: u64 = coins.iter().map(|coin| coin.balance).sum();
+        let needed = flag_coin_value.saturating_mul(flag_count);
+        if merged_balance < needed {
+            return Err(ChallengeError::InsufficientMintBalance {
+                needed,
+                available: merged_balance,
+            });
+        }
+        Ok(Self {
+            merged_balance,
+            flag_coin_value,
+            flag_count,
+        })
+    }
+
+    /// Sent back to the sender; zero when the merged coin is passed to `get_flag` without a split.
+    pub fn remainder(&self) -> u64 {
+        self.merged_balance - self.flag_coin_value * self.flag_count
+    }
+}
+
+/// The coins a run would use, as shown by `--explain`.
+#[derive(Debug, Clone)]
+pub struct CoinSelection {
+    pub gas_coin: Coin,
+    /// Empty while the sender owns too few MINTCOINs; transaction 2 then merges the ones transaction 1 mints.
+    pub mint_coins: Vec<Coin>,
+    pub split: Option<SplitPlan>,
+}
+
+/// Picks coins the way a run does: the first `mint_count` MINTCOINs, split by `SplitPlan`, and the largest gas coin
+/// covering `budget` that isn't one of them.
+pub fn select_coins(
+    gas_coins: Vec<Coin>,
+    mint_coins: Vec<Coin>,
+    mint_count: usize,
+    budget: u64,
+    flag_coin_value: u64,
+    flag_count: u64,
+) -> Result<CoinSelection, ChallengeError> {
+    let mint_coins = select_mint_coins(mint_coins, mint_count).unwrap_or_default();
+    let split = if mint_coins.is_empty() {
+        None
+    } else {
+        Some(SplitPlan::new(&mint_coins, flag_coin_value, flag_count)?)
+    };
+    let inputs: Vec<ObjectID> = mint_coins.iter().map(|coin| coin.coin_object_id).collect();
+    let gas_coin = pick_gas_coin(&gas_coins, budget, &inputs)?.clone();
+    Ok(CoinSelection {
+        gas_coin,
+        mint_coins,
+        split,
+    })
+}
+
+/// IDs of every object a PTB takes as input; none of them may also pay for gas.
+pub fn input_object_ids(pt: &ProgrammableTransaction) -> Vec<ObjectID> {
+    pt.inputs
+        .iter()
+        .filter_map(|input| match input {
+            CallArg::Object(ObjectArg::ImmOrOwnedObject((id, ..)))
+            | CallArg::Object(ObjectArg::Receiving((id, ..)))
+            | CallArg::Object(ObjectArg::SharedObject { id, .. }) => Some(*id),
+            CallArg::Pure(_) => None,
+        })
+        .collect()
+}
+
+/// Wraps `pt` into transaction data paid for by `gas_coin`, or by the largest `gas_coin_type` coin of the gas payer
+/// (`sender` unless sponsored) that covers the budget. The gas coin is never one of the PTB's own inputs, which would use the same object
+/// twice; this can happen on networks where the MINTCOIN is the gas coin type. A given `gas_coin` that is an
+/// input is replaced by a looked-up one.
+pub async fn attach_gas(
+    coin_source: &impl CoinSource,
+    sender: IotaAddress,
+    pt: ProgrammableTransaction,
+    gas_coin: Option<ObjectRef>,
+    gas_coin_type: Option<String>,
+    gas: &GasConfig,
+) -> Result<TransactionData, ChallengeError> {
+    let input_ids = input_object_ids(&pt);
+    let gas_coin = match gas_coin {
+        Some(gas_coin) if !input_ids.contains(&gas_coin.0) => gas_coin,
+        _ => {
+            let coins = gas.gas_coin_candidates(coin_source.get_coins(gas.payer(sender), gas_coin_type).await?);
+            pick_fresh_gas_coin(coin_source, &coins, gas.max_budget(), &input_ids).await?
+        }
+    };
+    Ok(gas.transaction(sender, gas_coin, pt))
+}
+
+/// Sets the gas budget of the built `stage` transaction, then signs, executes and reports it, failing unless it
+/// succeeded.
+pub async fn sign_and_execute(
+    client: &impl TxExecutor,
+    signer: &Signer,
+    mut tx_data: TransactionData,
+    stage: Stage,
+    gas: &GasConfig,
+    exec: &ExecuteConfig,
+    reporter: &dyn Reporter,
+) -> Result<IotaTransactionBlockResponse, ChallengeError> {
+    apply_gas_budget(client, &mut tx_data, gas, reporter).await?;
+    let label = stage.to_string().to_lowercase();
+
+    reporter.detail(&format!("Signing {}", label));
+    let signatures = signer.sign_transaction(&tx_data)?;
+
+    reporter.progress(&format!("Executing {}", label));
+    let response = execute_with_retry(
+        client,
+        Transaction::from_data(tx_data, signatures),
+        exec.response_options(),
+        exec.wait,
+        exec.max_attempts,
+    )
+    .await?;
+
+    reporter.executed(stage, &response);
+    assert_success(&response)?;
+    Ok(response)
+}
+
+/// Builds the unsigned transaction 1, calling `mintcoin::mint_coin` `count` times.
+pub async fn build_mint_tx(
+    client: &IotaClient,
+    deployment: &Deployment,
+    sender: IotaAddress,
+    gas_coin: ObjectRef,
+    gas: &GasConfig,
+    count: u32,
+    reporter: &dyn Reporter,
+) -> Result<TransactionData, ChallengeError> {
+    let treasury_cap = treasury_cap_arg(client, deployment.treasury_cap_id, sender, reporter).await?;
+    let pt = mint_ptb(deployment, treasury_cap, count, reporter)?;
+    Ok(gas.transaction(sender, gas_coin, pt))
+}
+
+/// The transaction 1 PTB: `count` calls of `mintcoin::mint_coin` on `treasury_cap`.
+pub fn mint_ptb(
+    deployment: &Deployment,
+    treasury_cap: ObjectArg,
+    count: u32,
+    reporter: &dyn Reporter,
+) -> Result<ProgrammableTransaction, ChallengeError> {
+    reporter.progress("--- Transaction 1: Mint MINTCOINs ---");
+    let mut ptb1 = ProgrammableTransactionBuilder::new();
+    let treasury_cap_arg = ptb1.input(CallArg::Object(treasury_cap))?;
+
+    for i in 1..=count {
+        move_call(&mut ptb1, deployment.package_id, "mintcoin", "mint_coin", vec![], vec![treasury_cap_arg])?;
+        reporter.detail(&format!("Command: mint_coin #{}", i));
+    }
+
+    Ok(ptb1.finish())
+}
+
+/// The transaction 2 PTB: merges `coins`, splits off `flag_count` coins of `flag_coin_value`, calls `get_flag` with
+/// each on the shared `counter` and sends the flag coins and the remainder to `recipient`. When `coins` hold exactly
+/// one `flag_coin_value`, the merged coin is passed as is, with no split and no remainder. The `Flag`s that `get_flag`
+/// returns are sent to `flag_recipient`, which has to be set exactly when `get_flag_returns_flag`: a returned `Flag`
+/// left unused fails the transaction.
+#[allow(clippy::too_many_arguments)]
+pub fn get_flag_ptb(
+    deployment: &Deployment,
+    counter: ObjectArg,
+    coins: &[Coin],
+    flag_coin_value: u64,
+    flag_count: u64,
+    recipient: IotaAddress,
+    flag_recipient: Option<IotaAddress>,
+    reporter: &dyn Reporter,
+) -> Result<ProgrammableTransaction, ChallengeError> {
+    let split = SplitPlan::new(coins, flag_coin_value, flag_count)?;
+
+    reporter.progress("--- Transaction 2: Merge, split & get flag ---");
+    let mut ptb2 = ProgrammableTransactionBuilder::new();
+
+    let counter_arg = ptb2.input(CallArg::Object(counter))?;
+
+    let coin_args = coins
+        .iter()
+        .map(|coin| ptb2.input(CallArg::Object(ObjectArg::ImmOrOwnedObject(coin.object_ref()))))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    claim_flag(
+        &mut ptb2,
+        deployment,
+        counter_arg,
+        &coin_args,
+        Some(split.merged_balance),
+        flag_coin_value,
+        flag_count,
+        recipient,
+        flag_recipient,
+        reporter,
+    )?;
+    Ok(ptb2.finish())
+}
+
+/// The `--single-tx` PTB: mints `count` MINTCOINs and feeds the returned coins straight into the transaction 2
+/// commands, so nothing has to be indexed in between. Only valid when `mint_coin` returns the coin.
+#[allow(clippy::too_many_arguments)]
+pub fn single_tx_ptb(
+    deployment: &Deployment,
+    treasury_cap: ObjectArg,
+    counter: ObjectArg,
+    count: u32,
+    flag_coin_value: u64,
+    flag_count: u64,
+    recipient: IotaAddress,
+    flag_recipient: Option<IotaAddress>,
+    reporter: &dyn Reporter,
+) -> Result<ProgrammableTransaction, ChallengeError> {
+    reporter.progress("--- Single transaction: Mint, merge, split & get flag ---");
+    let mut ptb = ProgrammableTransactionBuilder::new();
+    let treasury_cap_arg = ptb.input(CallArg::Object(treasury_cap))?;
+    let counter_arg = ptb.input(CallArg::Object(counter))?;
+
+    let mut coin_args = Vec::new();
+    for i in 1..=count {
+        coin_args.push(move_call(
+            &mut ptb,
+            deployment.package_id,
+            "mintcoin",
+            "mint_coin",
+            vec![],
+            vec![treasury_cap_arg],
+        )?);
+        reporter.detail(&format!("Command: mint_coin #{}", i));
+    }
+
+    claim_flag(
+        &mut ptb,
+        deployment,
+        counter_arg,
+        &coin_args,
+        // Minted amounts aren't known until execution, so the split always happens.
+        None,
+        flag_coin_value,
+        flag_count,
+        recipient,
+        flag_recipient,
+        reporter,
+    )?;
+    Ok(ptb.finish())
+}
+
+/// Merges `coin_args`, splits off `flag_count` coins of `flag_coin_value`, calls `get_flag` with each on
+/// `counter_arg` and sends the flag coins and the remainder to `recipient`, and the returned `Flag`s to
+/// `flag_recipient` if given. A `merged_balance` known to equal a single `flag_coin_value` skips the split, leaving
+/// no zero-value remainder to transfer.
+#[allow(clippy::too_many_arguments)]
+fn claim_flag(
+    ptb: &mut ProgrammableTransactionBuilder,
+    deployment: &Deployment,
+    counter_arg: Argument,
+    coin_args: &[Argument],
+    merged_balance: Option<u64>,
+    flag_coin_value: u64,
+    flag_count: u64,
+    recipient: IotaAddress,
+    flag_recipient: Option<IotaAddress>,
+    reporter: &dyn Reporter,
+) -> Result<(), ChallengeError> {
+    let mintcoin_type_tag = TypeTag::Struct(Box::new(StructTag {
+        address: AccountAddress::from(deployment.package_id),
+        module: MoveIdentifier::new(deployment.coin_module.as_str())?,
+        name: MoveIdentifier::new(deployment.coin_struct.as_str())?,
+        type_params: vec![],
+    }));
+
+    let merged_coin = merge_all(ptb, coin_args, &mintcoin_type_tag)?;
+    reporter
+        .detail(&format!("Command: join {} coins into coin1", coin_args.len() - 1));
+
+    // Split off exactly the flag coin values, unless the merged coin already is the only flag coin
+    let (flag_coins, remainder) = if flag_count == 1 && merged_balance == Some(flag_coin_value) {
+        reporter.detail(&format!("Merged coin holds exactly {}, no split needed", flag_coin_value));
+        (vec![merged_coin], None)
+    } else {
+        let flag_coins = split_n(ptb, merged_coin, flag_coin_value, flag_count, &mintcoin_type_tag)?;
+        reporter
+            .detail(&format!("Command: split(merged_coin, {}) x{}", flag_coin_value, flag_count));
+        (flag_coins, Some(merged_coin))
+    };
+
+    // get flag, once per flag coin
+    let mut flags = Vec::with_capacity(flag_coins.len());
+    for &flag_coin in &flag_coins {
+        let args = vec![counter_arg, flag_coin];
+        flags.push(move_call(ptb, deployment.package_id, "mintcoin", "get_flag", vec![], args)?);
+        reporter.detail("Command: get_flag(counter, flag_coin)");
+    }
+
+    // transfer back, unless get_flag consumed the coin: a moved value can't be transferred
+    let addr_arg = ptb.input(CallArg::Pure(bcs::to_bytes(&recipient)?))?;
+
+    if deployment.get_flag_consumes_coin == Some(true) {
+        reporter.detail("get_flag consumed flag_coin, nothing to transfer back");
+    } else {
+        ptb.command(Command::TransferObjects(
+            flag_coins,
+            addr_arg,
+        ));
+        reporter.detail("Command: transfer_objects(flag_coin, sender)");
+    }
+
+    // Send remaining coin back to ourselves too
+    if let Some(remainder) = remainder {
+        ptb.command(Command::TransferObjects(
+            vec![remainder],
+            addr_arg,
+        ));
+        reporter.detail("Command: transfer_objects(remaining_coin, sender)");
+    }
+
+    if let Some(flag_recipient) = flag_recipient {
+        let flag_recipient_arg = ptb.input(CallArg::Pure(bcs::to_bytes(&flag_recipient)?))?;
+        ptb.command(Command::TransferObjects(flags, flag_recipient_arg));
+        reporter.detail(&format!("Command: transfer_objects(flag, {})", flag_recipient));
+    }
+
+    Ok(())
+}
+
+/// Splits `n` coins of `amount` off `coin` with `coin::split`, one call each, and returns them in order. `coin` must
+/// hold at least `amount * n` of `type_tag`, or execution aborts.
+pub fn split_n(
+    ptb: &mut ProgrammableTransactionBuilder,
+    coin: Argument,
+    amount: u64,
+    n: u64,
+    type_tag: &TypeTag,
+) -> Result<Vec<Argument>, ChallengeError> {
+    let amount_arg = ptb.input(CallArg::Pure(bcs::to_bytes(&amount)?))?;
+    (0..n)
+        .map(|_| {
+            move_call(
+                ptb,
+                IOTA_FRAMEWORK_PACKAGE_ID,
+                "coin",
+                "split",
+                vec![type_tag.clone()],
+                vec![coin, amount_arg],
+            )
+        })
+        .collect()
+}
+
+/// Checks that `package` is a Move package whose `mintcoin` module exposes `mint_coin` and `get_flag`, so a wrong
+/// `--package-id` fails with a clear error instead of an abort at execution.
+pub async fn verify_package(client: &IotaClient, package: ObjectID) -> Result<(), ChallengeError> {
+    let response = client
+        .read_api()
+        .get_object_with_options(package, IotaObjectDataOptions::new().with_type())
+        .await?;
+    let data = response.data.ok_or(ChallengeError::ObjectNotFound(package))?;
+    if !matches!(data.type_, Some(ObjectType::Package)) {
+        return Err(ChallengeError::NotAPackage(package));
+    }
+    let modules = client
+        .read_api()
+        .get_normalized_move_modules_by_package(package)
+        .await?;
+    let module = modules
+        .get("mintcoin")
+        .ok_or(ChallengeError::MissingModule { package, module: "mintcoin" })?;
+    for function in ["mint_coin", "get_flag"] {
+        if !module.exposed_functions.contains_key(function) {
+            return Err(ChallengeError::MissingFunction {
+                package,
+                module: "mintcoin",
+                function,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Whether `mintcoin::mint_coin` returns the minted coin, which `--single-tx` needs to chain it into `get_flag`.
+/// When it doesn't, the coin is transferred inside `mint_coin` and only the two-transaction flow works.
+pub async fn mint_coin_returns_coin(client: &IotaClient, deployment: &Deployment) -> Result<bool, ChallengeError> {
+    let modules = client
+        .read_api()
+        .get_normalized_move_modules_by_package(deployment.package_id)
+        .await?;
+    let function = modules
+        .get("mintcoin")
+        .and_then(|module| module.exposed_functions.get("mint_coin"));
+    Ok(function.is_some_and(|function| function.return_.len() == 1))
+}
+
+/// Whether `mintcoin::get_flag` returns the `Flag`, which `--flag-recipient` needs to send it elsewhere. When it
+/// doesn't, `get_flag` transfers the flag to the sender itself.
+pub async fn get_flag_returns_flag(client: &IotaClient, deployment: &Deployment) -> Result<bool, ChallengeError> {
+    let modules = client
+        .read_api()
+        .get_normalized_move_modules_by_package(deployment.package_id)
+        .await?;
+    let function = modules
+        .get("mintcoin")
+        .and_then(|module| module.exposed_functions.get("get_flag"));
+    Ok(function.is_some_and(|function| function.return_.len() == 1))
+}
+
+/// Whether `mintcoin::get_flag` takes a `Coin` by value and so consumes the flag coin. One taken by reference stays
+/// in the PTB after the call and has to be transferred back.
+pub async fn get_flag_consumes_coin(client: &IotaClient, package: ObjectID) -> Result<bool, ChallengeError> {
+    let modules = client
+        .read_api()
+        .get_normalized_move_modules_by_package(package)
+        .await?;
+    let function = modules
+        .get("mintcoin")
+        .and_then(|module| module.exposed_functions.get("get_flag"));
+    let takes_coin = |parameter: &IotaMoveNormalizedType| {
+        matches!(parameter, IotaMoveNormalizedType::Struct { module, name, .. } if module == "coin" && name == "Coin")
+    };
+    Ok(function.is_some_and(|function| function.parameters.iter().any(takes_coin)))
+}
+
+/// Appends a `<package>::<module>::<function>` call to `ptb` and returns the argument for its result.
+pub fn move_call(
+    ptb: &mut ProgrammableTransactionBuilder,
+    package: ObjectID,
+    module: &str,
+    function: &str,
+    type_arguments: Vec<TypeTag>,
+    arguments: Vec<Argument>,
+) -> Result<Argument, ChallengeError> {
+    Ok(ptb.command(Command::MoveCall(Box::new(ProgrammableMoveCall {
+        package,
+        module: Identifier::new(module)?,
+        function: Identifier::new(function)?,
+        type_arguments,
+        arguments,
+    }))))
+}
+
+/// Joins every coin in `coins` into the first one with `coin::join` and returns that accumulator.
+pub fn merge_all(
+    ptb: &mut ProgrammableTransactionBuilder,
+    coins: &[Argument],
+    type_tag: &TypeTag,
+) -> Result<Argument, ChallengeError> {
+    let (&primary, rest) = coins.split_first().ok_or(ChallengeError::NoCoinsToMerge)?;
+    for &coin in rest {
+        move_call(ptb, IOTA_FRAMEWORK_PACKAGE_ID, "coin", "join", vec![type_tag.clone()], vec![primary, coin])?;
+    }
+    Ok(primary)
+}
+
+/// Finds the `<package>::mintcoin::Flag` object a transaction created for an address, usually its sender, with its
+/// type.
+///
+/// Requires the response to have been requested with object changes.
+pub fn extract_flag_object(
+    response: &IotaTransactionBlockResponse,
+    package: ObjectID,
+) -> Option<(ObjectID, StructTag)> {
+    response.object_changes.as_ref()?.iter().find_map(|change| match change {
+        ObjectChange::Created {
+            owner: Owner::AddressOwner(_),
+            object_id,
+            object_type,
+            ..
+        } if object_type.address == AccountAddress::from(package)
+            && object_type.module.as_str() == "mintcoin"
+            && object_type.name.as_str() == "Flag" =>
+        {
+            Some((*object_id, object_type.clone()))
+        }
+        _ => None,
+    })
+}
+
+/// One row of the `--verbose-changes` table: what happened to which object.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ObjectChangeSummary {
+    /// `created`, `mutated`, `deleted`, `wrapped`, `transferred` or `published`.
+    pub kind: &'static str,
+    pub object_id: ObjectID,
+    /// Fully qualified Move type, or `package` for a published package.
+    pub object_type: String,
+}
+
+/// Flattens the object changes of `response` into one row per object, in the order the node reported them.
+///
+/// Requires the response to have been requested with object changes.
+pub fn summarize_object_changes(response: &IotaTransactionBlockResponse) -> Vec<ObjectChangeSummary> {
+    let Some(changes) = &response.object_changes else {
+        return Vec::new();
+    };
+    changes
+        .iter()
+        .map(|change| {
+            let (kind, object_id, object_type) = match change {
+                ObjectChange::Created { object_id, object_type, .. } => ("created", object_id, object_type.to_string()),
+                ObjectChange::Mutated { object_id, object_type, .. } => ("mutated", object_id, object_type.to_string()),
+                ObjectChange::Deleted { object_id, object_type, .. } => ("deleted", object_id, object_type.to_string()),
+                ObjectChange::Wrapped { object_id, object_type, .. } => ("wrapped", object_id, object_type.to_string()),
+                ObjectChange::Transferred { object_id, object_type, .. } => {
+                    ("transferred", object_id, object_type.to_string())
+                }
+                ObjectChange::Published { package_id, .. } => ("published", package_id, "package".to_string()),
+            };
+            ObjectChangeSummary {
+                kind,
+                object_id: *object_id,
+                object_type,
+            }
+        })
+        .collect()
+}
+
+/// Object counts, net gas and status of a transaction, reported on one line instead of dumping its effects.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EffectsSummary {
+    pub created: usize,
+    pub mutated: usize,
+    pub deleted: usize,
+    /// Computation plus storage cost, minus the storage rebate, in NANOS.
+    pub gas_used: u64,
+    /// Execution error, `None` when the transaction succeeded.
+    pub error: Option<String>,
+}
+
+impl fmt::Display for EffectsSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "created {}, mutated {}, deleted {}, gas {:.1} mIOTA, ",
+            self.created,
+            self.mutated,
+            self.deleted,
+            self.gas_used as f64 / 1_000_000.0
+        )?;
+        match &self.error {
+            None => write!(f, "success"),
+            Some(error) => write!(f, "failure: {}", error),
+        }
+    }
+}
+
+/// Summarizes executed or dry-run `effects`.
+pub fn summarize_effects(effects: &IotaTransactionBlockEffects) -> EffectsSummary {
+    EffectsSummary {
+        created: effects.created().len(),
+        mutated: effects.mutated().len(),
+        deleted: effects.deleted().len(),
+        gas_used: net_gas_used(effects.gas_cost_summary()),
+        error: match effects.status() {
+            IotaExecutionStatus::Success => None,
+            IotaExecutionStatus::Failure { error } => Some(error.clone()),
+        },
+    }
+}
+
+/// References of the objects a transaction created, taken from its effects. For transaction 1 these are exactly
+/// the minted MINTCOINs.
+pub fn created_object_refs(response: &IotaTransactionBlockResponse) -> Result<Vec<ObjectRef>, ChallengeError> {
+    let effects = response
+        .effects
+        .as_ref()
+        .ok_or(ChallengeError::MissingEffects(response.digest))?;
+    Ok(effects
+        .created()
+        .iter()
+        .map(|created| created.reference.to_object_ref())
+        .collect())
+}
+
+/// The gas coin's object reference after a transaction, so the next one can pay with it without a lookup.
+pub fn gas_object_ref_after(effects: &IotaTransactionBlockEffects) -> Result<ObjectRef, ChallengeError> {
+    let gas_object = effects.gas_object();
+    match gas_object.owner {
+        Owner::AddressOwner(_) => Ok(gas_object.reference.to_object_ref()),
+        _ => Err(ChallengeError::GasObjectNotOwned(gas_object.reference.object_id)),
+    }
+}
+
+/// Fails unless the transaction's effects report successful execution.
+pub fn assert_success(response: &IotaTransactionBlockResponse) -> Result<(), ChallengeError> {
+    let effects = response
+        .effects
+        .as_ref()
+        .ok_or(ChallengeError::MissingEffects(response.digest))?;
+    match effects.status() {
+        IotaExecutionStatus::Success => Ok(()),
+        IotaExecutionStatus::Failure { error } => Err(ChallengeError::ExecutionFailed {
+            digest: response.digest,
+            abort: parse_move_abort(error),
+            error: error.clone(),
+        }),
+    }
+}
+
+/// Where a Move abort happened and with which code.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MoveAbort {
+    /// Module that aborted, e.g. `mintcoin`.
+    pub module: Option<String>,
+    /// Function that aborted, e.g. `get_flag`.
+    pub function: Option<String>,
+    pub code: u64,
+}
+
+impl MoveAbort {
+    /// The abort behind a failed execution `status`, if it was one.
+    pub fn from_status(status: &IotaExecutionStatus) -> Option<Self> {
+        match status {
+            IotaExecutionStatus::Success => None,
+            IotaExecutionStatus::Failure { error } => parse_move_abort(error),
+        }
+    }
+
+    /// The likely cause of aborts this tool knows about.
+    pub fn hint(&self) -> Option<&'static str> {
+        match (self.module.as_deref(), self.function.as_deref()) {
+            (Some("mintcoin"), Some("get_flag")) => Some("likely wrong coin value; see --flag-coin-value"),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for MoveAbort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} aborted", self.function.as_deref().unwrap_or("Move code"))?;
+        if let Some(module) = &self.module {
+            write!(f, " in {}", module)?;
+        }
+        write!(f, " with code {}", self.code)?;
+        if let Some(hint) = self.hint() {
+            write!(f, " ({})", hint)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a failure such as `MoveAbort(MoveLocation { module: ModuleId { .., name: Identifier("mintcoin") }, ..,
+/// function_name: Some("get_flag") }, 3) in command 3`. Only the code is required; any other failure is `None`.
+pub fn parse_move_abort(error: &str) -> Option<MoveAbort> {
+    let start = error.find("MoveAbort(")? + "MoveAbort(".len();
+    let mut depth = 0usize;
+    for (offset, c) in error[start..].char_indices() {
+        match c {
+            '(' | '{' => depth += 1,
+            ')' | '}' if depth > 0 => depth -= 1,
+            ')' => {
+                let args = &error[start..start + offset];
+                return Some(MoveAbort {
+                    module: quoted_after(args, "name: Identifier(\""),
+                    function: quoted_after(args, "function_name: Some(\""),
+                    code: args.rsplit(',').next()?.trim().parse().ok()?,
+                });
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The text between `prefix` and the next double quote.
+fn quoted_after(s: &str, prefix: &str) -> Option<String> {
+    let start = s.find(prefix)? + prefix.len();
+    let len = s[start..].find('"')?;
+    Some(s[start..start + len].to_string())
+}
+
+/// Pulls the abort code out of a failure such as `MoveAbort(MoveLocation { .. }, 3) in command 3`.
+pub fn move_abort_code(error: &str) -> Option<u64> {
+    parse_move_abort(error).map(|abort| abort.code)
+}
+
+/// Computation plus storage cost, minus the storage rebate, floored at zero.
+pub fn net_gas_used(gas_cost: &GasCostSummary) -> u64 {
+    (gas_cost.computation_cost + gas_cost.storage_cost).saturating_sub(gas_cost.storage_rebate)
+}
+
+/// Computation, storage and rebate of several transactions added up.
+pub fn total_gas_cost<'a>(costs: impl IntoIterator<Item = &'a GasCostSummary>) -> GasCostSummary {
+    costs.into_iter().fold(GasCostSummary::default(), |total, cost| GasCostSummary {
+        computation_cost: total.computation_cost + cost.computation_cost,
+        computation_cost_burned: total.computation_cost_burned + cost.computation_cost_burned,
+        storage_cost: total.storage_cost + cost.storage_cost,
+        storage_rebate: total.storage_rebate + cost.storage_rebate,
+        non_refundable_storage_fee: total.non_refundable_storage_fee + cost.non_refundable_storage_fee,
+    })
+}
+
+/// Final result of a full challenge run.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunSummary {
+    /// `None` when transaction 1 was skipped because enough MINTCOINs already existed.
+    pub tx1_digest: Option<TransactionDigest>,
+    pub tx2_digest: TransactionDigest,
+    pub flag_obtained: bool,
+    pub tx1_gas_used: Option<u64>,
+    pub tx2_gas_used: u64,
+    /// Net gas used by both transactions together.
+    pub gas_used: u64,
+    /// Costs of both transactions together, of which `gas_used` is computation plus storage minus rebate.
+    pub gas_cost: GasCostSummary,
+}
+
+/// Durable record of a full run, written by `--receipt`.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunReceipt {
+    pub sender: IotaAddress,
+    pub rpc_url: String,
+    /// `None` when transaction 1 was skipped because enough MINTCOINs already existed.
+    pub tx1_digest: Option<TransactionDigest>,
+    pub tx2_digest: TransactionDigest,
+    pub tx1_gas_used: Option<u64>,
+    pub tx2_gas_used: u64,
+    pub flag_object: Option<ObjectID>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl RunReceipt {
+    /// Writes the receipt to `path` as pretty-printed JSON.
+    pub fn write(&self, path: &Path) -> Result<(), ChallengeError> {
+        let json = serde_json::to_string_pretty(self).map_err(anyhow::Error::from)?;
+        std::fs::write(path, json).map_err(|source| ChallengeError::ReceiptWrite {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+}
+
+/// Projected outcome of a transaction that was simulated rather than executed.
+#[derive(Debug)]
+pub struct DryRunResult {
+    pub status: IotaExecutionStatus,
+    /// Computation plus storage cost, minus the storage rebate.
+    pub gas_used: u64,
+    pub gas_cost: GasCostSummary,
+    pub effects: IotaTransactionBlockEffects,
+}
+
+impl DryRunResult {
+    /// The Move abort (or other execution error) message, if the simulation failed.
+    pub fn abort(&self) -> Option<&str> {
+        match &self.status {
+            IotaExecutionStatus::Success => None,
+            IotaExecutionStatus::Failure { error } => Some(error),
+        }
+    }
+
+    /// Where the simulation aborted, if it failed with a Move abort.
+    pub fn move_abort(&self) -> Option<MoveAbort> {
+        MoveAbort::from_status(&self.status)
+    }
+}
+
+/// Simulates `tx_data` with `dry_run_transaction_block` without signing or spending gas.
+pub async fn dry_run(client: &impl TxExecutor, tx_data: &TransactionData) -> Result<DryRunResult, ChallengeError> {
+    let effects = client.dry_run_transaction_block(tx_data.clone()).await?;
+    let gas_cost = effects.gas_cost_summary().clone();
+
+    Ok(DryRunResult {
+        status: effects.status().clone(),
+        gas_used: net_gas_used(&gas_cost),
+        gas_cost,
+        effects,
+    })
+}
+
+/// Dry-runs `tx_data` and returns its gas budget from `gas_budget`.
+///
+/// Fails with `ExecutionFailed` instead when the simulation aborts, so the transaction is never submitted.
+pub async fn estimate_gas_budget(
+    client: &impl TxExecutor,
+    tx_data: &TransactionData,
+    multiplier: f64,
+) -> Result<u64, ChallengeError> {
+    let result = dry_run(client, tx_data).await?;
+    if let Some(error) = result.abort() {
+        return Err(ChallengeError::ExecutionFailed {
+            digest: *result.effects.transaction_digest(),
+            abort: parse_move_abort(error),
+            error: error.to_string(),
+        });
+    }
+    Ok(gas_budget(&result.gas_cost, tx_data.gas_price(), multiplier))
+}
+
+/// Reference gas price units added on top of the computation cost, as the reference SDKs do.
+pub const GAS_SAFE_OVERHEAD: u64 = 1000;
+
+/// Fails when the reference gas price `reference` exceeds `cap`, so a fee spike doesn't go unnoticed.
+pub fn check_gas_price(reference: u64, cap: Option<u64>) -> Result<(), ChallengeError> {
+    match cap {
+        Some(cap) if reference > cap => Err(ChallengeError::GasPriceTooHigh { reference, cap }),
+        _ => Ok(()),
+    }
+}
+
+/// Net gas usage scaled by `multiplier`, but never below the computation cost plus `GAS_SAFE_OVERHEAD` units of
+/// `gas_price`: a storage rebate that cancels out the storage cost would otherwise leave too little for computation.
+pub fn gas_budget(gas_cost: &GasCostSummary, gas_price: u64, multiplier: f64) -> u64 {
+    let estimate = (net_gas_used(gas_cost) as f64 * multiplier).ceil() as u64;
+    let floor = gas_cost
+        .computation_cost
+        .saturating_add(GAS_SAFE_OVERHEAD.saturating_mul(gas_price));
+    estimate.max(floor)
+}
+
+/// Sets the configured budget on `tx_data`, estimating one from a dry run when none is fixed.
+pub(crate) async fn apply_gas_budget(
+    client: &impl TxExecutor,
+    tx_data: &mut TransactionData,
+    gas: &GasConfig,
+    reporter: &dyn Reporter,
+) -> Result<(), ChallengeError> {
+    let budget = match gas.budget {
+        Some(budget) => budget,
+        None => {
+            let estimate = estimate_gas_budget(client, tx_data, gas.multiplier).await?;
+            reporter.detail(&format!("Estimated gas budget: {}", estimate));
+            estimate
+        }
+    };
+    tx_data.gas_data_mut().budget = budget;
+    Ok(())
+}
@@ -1,251 +1,362 @@
-use iota_sdk::{
-    IotaClientBuilder,
-    types::{
-        base_types::ObjectID,
-        programmable_transaction_builder::ProgrammableTransactionBuilder,
-        transaction::{Command, TransactionData, CallArg, ObjectArg, ProgrammableMoveCall},
-        Identifier,
-    },
-    rpc_types::IotaTransactionBlockResponseOptions,
+use clap::Parser;
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing_subscriber::EnvFilter;
+use iota_challenge3_example::{
+    ChallengeClient, ChallengeConfig, ChallengeError, ChallengeOutcome, ChallengeRunner, Confirm, FailoverClient,
+    HumanReporter, JsonReporter, Reporter, RunReceipt, RunSummary, ExecuteConfig, Signer, Stage, Step, check_gas_price,
+    offline,
+    report::{TrackingReporter, error_chain},
+    select_mint_coins,
+    signer::{KEYSTORE_PATH_ENV, PRIVATE_KEY_ENV},
 };
-use iota_keys::keystore::{AccountKeystore, FileBasedKeystore};
-use shared_crypto::intent::Intent;
-use move_core_types::{
-    language_storage::{TypeTag, StructTag},
-    account_address::AccountAddress,
-    identifier::Identifier as MoveIdentifier,
+use chrono::Utc;
+use iota_sdk::types::{
+    crypto::Signature,
+    transaction::{TransactionData, TransactionDataAPI, TransactionKind},
 };
-use std::str::FromStr;
-use std::time::Duration;
-use bcs;
 
-const PACKAGE_ID: &str = "0xc6f00a2b5ec2d161442b305dcb307ba914e20c5268ec931bd14d7ea3454b262b";
-const TREASURY_CAP_ID: &str = "0x11d7aacb27eb65063dbb6ce0fa07f7807316c5e77763c6f2356d1bd3a34a2741";
-const SHARED_COUNTER_ID: &str = "0xc3716689fa16bd8d8bf33ce1036b00740c8818ab9826dba846ef736501fd34b7";
+mod cli;
+mod doctor;
+
+use cli::{Args, Command, GetFlagArgs, MintArgs, RunArgs, SignArgs, SubmitArgs, TxArgs};
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    println!("Challenge 3: Starting multi-transaction flow");
-
-    println!("Connecting to IOTA testnet");
-    let client = IotaClientBuilder::default()
-        .build("https://api.testnet.iota.cafe")
-        .await?;
-    println!("Connected to IOTA testnet");
-
-    println!("Loading keystore");
-    let keystore_path = dirs::home_dir()
-        .ok_or("Failed to get home directory")?
+async fn main() -> ExitCode {
+    let args = Args::parse();
+    init_tracing(args.quiet);
+
+    let output: Arc<dyn Reporter> = if args.json {
+        Arc::new(JsonReporter)
+    } else {
+        Arc::new(HumanReporter)
+    };
+    let tracker = Arc::new(TrackingReporter::new(output));
+    let reporter: Arc<dyn Reporter> = tracker.clone();
+
+    let run = async {
+        match &args.command {
+            Command::RunAll(run_args) => run_all(&args, run_args, reporter.clone()).await,
+            Command::Mint(mint_args) => mint(&args, mint_args, reporter.clone()).await,
+            Command::GetFlag(get_flag_args) => get_flag(&args, get_flag_args, reporter.clone()).await,
+            Command::Balance => balance(&args, reporter.clone()).await,
+            Command::Doctor => doctor::run(&args, reporter.as_ref()).await,
+            Command::Sign(sign_args) => sign(&args, sign_args, reporter.as_ref()),
+            Command::Submit(submit_args) => submit(&args, submit_args, reporter.as_ref()).await,
+        }
+    };
+    // On Ctrl-C, stop waiting and report the digests of the transactions that already went through.
+    let result = tokio::select! {
+        result = run => result,
+        _ = tokio::signal::ctrl_c() => Err(ChallengeError::Interrupted { completed: tracker.completed() }),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            reporter.error(&error);
+            // Straight to stderr rather than through tracing, so neither --quiet nor RUST_LOG=off hides it.
+            eprintln!("Error: {}", error_chain(&error).join(": "));
+            ExitCode::from(error.exit_code())
+        }
+    }
+}
+
+/// Logs to stderr at `info` (`warn` when quiet) unless `RUST_LOG` says otherwise.
+fn init_tracing(quiet: bool) {
+    let default_level = if quiet { "warn" } else { "info" };
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default_level)))
+        .with_target(false)
+        .with_writer(std::io::stderr)
+        .init();
+}
+
+/// Every `--keystore-path`, else `IOTA_KEYSTORE_PATH`, else the IOTA CLI's keystore in the home directory.
+fn keystore_paths(args: &Args) -> Result<Vec<PathBuf>, ChallengeError> {
+    if !args.keystore_path.is_empty() {
+        return Ok(args.keystore_path.clone());
+    }
+    if let Ok(path) = std::env::var(KEYSTORE_PATH_ENV) {
+        return Ok(vec![PathBuf::from(path)]);
+    }
+    Ok(vec![dirs::home_dir()
+        .ok_or(ChallengeError::HomeDirNotFound)?
         .join(".iota")
         .join("iota_config")
-        .join("iota.keystore");
-    
-    let keystore = FileBasedKeystore::new(&keystore_path)?;
-    let addresses = keystore.addresses();
-    if addresses.is_empty() {
-        return Err("No addresses in keystore".into());
-    }
-    let sender_address = addresses[0];
-    println!("Using address: {}", sender_address);
-
-    println!("Getting coins for gas");
-    let coins = client
-        .coin_read_api()
-        .get_coins(sender_address, None, None, None)
-        .await?;
-    
-    let gas_coin = coins.data.get(0).ok_or("No coins found for gas")?;
-    println!("Found {} gas coins", coins.data.len());
-    
-    println!("Getting gas price");
-    let gas_price = client.read_api().get_reference_gas_price().await?;
-    println!("Gas price: {}", gas_price);
-
-    // mint coins
-    println!("\n--- Transaction 1: Mint MINTCOINs ---");
-    let mut ptb1 = ProgrammableTransactionBuilder::new();
-
-    let treasury_cap_arg = ptb1.input(CallArg::Object(ObjectArg::SharedObject {
-        id: ObjectID::from_str(TREASURY_CAP_ID)?,
-        initial_shared_version: iota_sdk::types::base_types::SequenceNumber::from_u64(6286155),
-        mutable: true,
-    }))?;
-
-    // mint 3 coins
-    for i in 1..=3 {
-        ptb1.command(Command::MoveCall(Box::new(ProgrammableMoveCall {
-            package: ObjectID::from_str(PACKAGE_ID)?,
-            module: Identifier::new("mintcoin")?,
-            function: Identifier::new("mint_coin")?,
-            type_arguments: vec![],
-            arguments: vec![treasury_cap_arg],
-        })));
-        println!("  - Command: mint_coin #{}", i);
-    }
-    
-    let tx_data1 = TransactionData::new_programmable(
-        sender_address,
-        vec![gas_coin.object_ref()],
-        ptb1.finish(),
-        50_000_000,
-        gas_price,
-    );
-    
-    println!("Signing transaction 1");
-    let signature1 = keystore.sign_secure(&sender_address, &tx_data1, Intent::iota_transaction())?;
-    
-    println!("Executing transaction 1");
-    let response1 = client
-        .quorum_driver_api()
-        .execute_transaction_block(
-            iota_sdk::types::transaction::Transaction::from_data(tx_data1, vec![signature1]),
-            IotaTransactionBlockResponseOptions::full_content(),
-            Some(iota_sdk::types::quorum_driver_types::ExecuteTransactionRequestType::WaitForLocalExecution),
-        )
-        .await?;
-
-    println!("Transaction 1 executed");
-    println!("Transaction digest: {:?}", response1.digest);
-
-    if let Some(effects) = &response1.effects {
-        println!("Transaction 1 effects: {:#?}", effects);
-    }
-    println!("Transaction 1 sent! (Please check if successful)");
-
-    // wait for sync
-    println!("\nWaiting 5 seconds for network sync");
-    tokio::time::sleep(Duration::from_secs(5)).await;
-
-    println!("Looking for newly minted MINTCOINs");
-    let coin_type = format!("{}::mintcoin::MINTCOIN", PACKAGE_ID);
-    let mint_coins = client
-        .coin_read_api()
-        .get_coins(sender_address, Some(coin_type), None, None)
-        .await?;
-
-    if mint_coins.data.len() < 3 {
-        return Err(format!("Not enough MINTCOINs. Expected >= 3, found {}", mint_coins.data.len()).into());
-    }
-    println!("Found {} MINTCOINs", mint_coins.data.len());
-
-    let coin_ref1 = mint_coins.data[0].object_ref();
-    let coin_ref2 = mint_coins.data[1].object_ref();
-    let coin_ref3 = mint_coins.data[2].object_ref();
-    
-    // merge, split, get flag
-    println!("\n--- Transaction 2: Merge, split & get flag ---");
-    let mut ptb2 = ProgrammableTransactionBuilder::new();
-
-    let mintcoin_type_tag = TypeTag::Struct(Box::new(StructTag {
-        address: AccountAddress::from_str(PACKAGE_ID)?,
-        module: MoveIdentifier::new("mintcoin")?,
-        name: MoveIdentifier::new("MINTCOIN")?,
-        type_params: vec![],
-    }));
-    
-    let counter_arg = ptb2.input(CallArg::Object(ObjectArg::SharedObject {
-        id: ObjectID::from_str(SHARED_COUNTER_ID)?,
-        initial_shared_version: iota_sdk::types::base_types::SequenceNumber::from_u64(6286155),
-        mutable: true,
-    }))?;
-
-    let coin1_arg = ptb2.input(CallArg::Object(ObjectArg::ImmOrOwnedObject(coin_ref1)))?;
-    let coin2_arg = ptb2.input(CallArg::Object(ObjectArg::ImmOrOwnedObject(coin_ref2)))?;
-    let coin3_arg = ptb2.input(CallArg::Object(ObjectArg::ImmOrOwnedObject(coin_ref3)))?;
-
-    // join coins
-    ptb2.command(Command::MoveCall(Box::new(ProgrammableMoveCall {
-        package: ObjectID::from_str("0x2")?,
-        module: Identifier::new("coin")?,
-        function: Identifier::new("join")?,
-        type_arguments: vec![mintcoin_type_tag.clone()],
-        arguments: vec![coin1_arg, coin2_arg],
-    })));
-    println!("  - Command: join(coin1, coin2)");
-
-    ptb2.command(Command::MoveCall(Box::new(ProgrammableMoveCall {
-        package: ObjectID::from_str("0x2")?,
-        module: Identifier::new("coin")?,
-        function: Identifier::new("join")?,
-        type_arguments: vec![mintcoin_type_tag.clone()],
-        arguments: vec![coin1_arg, coin3_arg],
-    })));
-    println!("  - Command: join(coin1, coin3)");
-    
-    // Split to get exactly 5 units
-    let pure_data = bcs::to_bytes(&5u64)?; // We need exactly 5 units
-    let value_arg = ptb2.input(CallArg::Pure(pure_data))?;
-    let coin_with_5 = ptb2.command(Command::MoveCall(Box::new(ProgrammableMoveCall {
-        package: ObjectID::from_str("0x2")?, // Use standard coin package
-        module: Identifier::new("coin")?,
-        function: Identifier::new("split")?, // Split function to get exact amount
-        type_arguments: vec![mintcoin_type_tag.clone()],
-        arguments: vec![coin1_arg, value_arg], // Split 5 units from merged coin
-    })));
-    println!("  - Command: split(merged_coin, 5)");
-
-    // get flag
-    ptb2.command(Command::MoveCall(Box::new(ProgrammableMoveCall {
-        package: ObjectID::from_str(PACKAGE_ID)?,
-        module: Identifier::new("mintcoin")?,
-        function: Identifier::new("get_flag")?,
-        type_arguments: vec![],
-        arguments: vec![counter_arg, coin_with_5],
-    })));
-    println!("  - Command: get_flag(counter, coin_with_5)");
-    
-    // transfer back
-    let move_address = AccountAddress::from_str(&sender_address.to_string())?;
-    let addr_arg = ptb2.input(CallArg::Pure(bcs::to_bytes(&move_address)?))?;
-    
-    ptb2.command(Command::TransferObjects(
-        vec![coin_with_5],
-        addr_arg,
-    ));
-    println!("  - Command: transfer_objects(coin_with_5, sender)");
-    
-    // Send remaining coin back to ourselves too
-    ptb2.command(Command::TransferObjects(
-        vec![coin1_arg],
-        addr_arg,
-    ));
-    println!("  - Command: transfer_objects(remaining_coin, sender)");
-
-    // Get fresh gas coin for transaction 2
-    let gas_coins2 = client
-        .coin_read_api()
-        .get_coins(sender_address, None, None, None)
-        .await?;
-    let gas_coin2 = gas_coins2.data.get(0).ok_or("No coins found for gas for transaction 2")?;
-    
-    let tx_data2 = TransactionData::new_programmable(
-        sender_address,
-        vec![gas_coin2.object_ref()],
-        ptb2.finish(),
-        50_000_000,
-        gas_price,
-    );
-
-    println!("Signing transaction 2");
-    let signature2 = keystore.sign_secure(&sender_address, &tx_data2, Intent::iota_transaction())?;
-
-    println!("Executing transaction 2");
-    let response2 = client
-        .quorum_driver_api()
-        .execute_transaction_block(
-            iota_sdk::types::transaction::Transaction::from_data(tx_data2, vec![signature2]),
-            IotaTransactionBlockResponseOptions::full_content(),
-            Some(iota_sdk::types::quorum_driver_types::ExecuteTransactionRequestType::WaitForLocalExecution),
-        )
-        .await?;
-
-    println!("Transaction 2 executed");
-    println!("Transaction digest: {:?}", response2.digest);
-
-    if let Some(effects) = &response2.effects {
-        println!("Final transaction effects: {:#?}", effects);
-        println!("\nTransaction 2 completed! Check the effects above to confirm success!");
+        .join("iota.keystore")])
+}
+
+/// Signs with `IOTA_PRIVATE_KEY` when it is set, so no keystore file is needed, otherwise with the keystore.
+fn signer(args: &Args) -> Result<Signer, ChallengeError> {
+    match std::env::var(PRIVATE_KEY_ENV) {
+        Ok(key) => Signer::from_private_key(&key),
+        Err(_) => Signer::from_files(&keystore_paths(args)?),
+    }
+}
+
+/// With `--build-only` and `--sender`, builds for that address without loading any key, e.g. on an online machine
+/// while the key stays offline.
+fn building_signer(args: &Args, build_only: bool) -> Result<Signer, ChallengeError> {
+    match args.sender {
+        Some(sender) if build_only => Ok(Signer::WatchOnly(sender)),
+        _ => signer(args),
+    }
+}
+
+async fn connect(
+    args: &Args,
+    config: &ChallengeConfig,
+    signer: Signer,
+    reporter: Arc<dyn Reporter>,
+) -> Result<ChallengeClient, ChallengeError> {
+    ChallengeClient::connect(config, signer, args.sender_selection(), reporter)
+        .await
+        .map(|challenge| challenge.with_gas_coin_type(args.gas_coin_type.clone()))
+}
+
+async fn balance(args: &Args, reporter: Arc<dyn Reporter>) -> Result<(), ChallengeError> {
+    let config = args.config()?;
+    let challenge = connect(args, &config, signer(args)?, reporter).await?;
+    let balances = challenge.balances().await?;
+    challenge.reporter().balance(&balances);
+    Ok(())
+}
+
+/// Prompts before every transaction when stdin is a terminal, unless `--yes` was given; piped and CI runs are never
+/// blocked.
+fn confirmation(args: &Args) -> Option<Confirm> {
+    if args.yes || !std::io::stdin().is_terminal() {
+        return None;
+    }
+    Some(Arc::new(confirm))
+}
+
+/// Shows what `tx_data` will do and spend, then asks "Proceed? [y/N]"; anything but `y` declines.
+fn confirm(stage: Stage, tx_data: &TransactionData) -> bool {
+    eprintln!("{} from {}", stage, tx_data.sender());
+    if tx_data.gas_owner() != tx_data.sender() {
+        eprintln!("  gas paid by {}", tx_data.gas_owner());
+    }
+    eprintln!("  gas budget: {} at gas price {}", tx_data.gas_budget(), tx_data.gas_price());
+    if let TransactionKind::ProgrammableTransaction(pt) = tx_data.kind() {
+        for command in &pt.commands {
+            eprintln!("  {}", command);
+        }
+    }
+    eprint!("Proceed? [y/N] ");
+    let mut answer = String::new();
+    if std::io::stdin().read_line(&mut answer).is_err() {
+        return false;
+    }
+    answer.trim().eq_ignore_ascii_case("y")
+}
+
+/// Connects and applies the transaction options shared by every sending subcommand.
+async fn connect_for_tx(
+    args: &Args,
+    config: &ChallengeConfig,
+    tx: &TxArgs,
+    signer: Signer,
+    reporter: Arc<dyn Reporter>,
+) -> Result<ChallengeClient, ChallengeError> {
+    let challenge = connect(args, config, signer, reporter)
+        .await?
+        .with_gas_budget(tx.gas_budget)
+        .with_gas_multiplier(tx.gas_multiplier)
+        .with_flag_coin_value(config.flag_coin_value)
+        .with_flag_count(config.flag_count)
+        .with_max_retries(tx.max_retries)
+        .with_full_content(tx.full_content)
+        .with_wait_strategy(tx.wait_strategy)
+        .with_faucet_url(tx.faucet_url.clone().or_else(|| config.faucet_url.clone()))
+        .with_verbose_changes(tx.verbose_changes)
+        .with_verbose_effects(tx.verbose_effects)
+        .with_sponsor(tx.sponsor)
+        .with_sponsor_gas_coin(tx.sponsor_gas_coin)
+        .with_confirmation(confirmation(args))
+        .with_finality_timeout(tx.wait_for_finality.then(|| Duration::from_secs(tx.finality_timeout)));
+    check_gas_price(challenge.gas_price(), tx.max_gas_price)?;
+    // A watch-only `--build-only` run signs nothing, so the sponsor's key isn't needed there.
+    if let (Some(sponsor), false) = (tx.sponsor, matches!(challenge.signer(), Signer::WatchOnly(_))) {
+        let available = challenge.signer().addresses();
+        if !available.contains(&sponsor) {
+            return Err(ChallengeError::UnknownSponsor { sponsor, available });
+        }
+    }
+    Ok(challenge)
+}
+
+/// Transaction 1 only.
+async fn mint(args: &Args, mint_args: &MintArgs, reporter: Arc<dyn Reporter>) -> Result<(), ChallengeError> {
+    let config = args.config()?;
+    let signer = building_signer(args, mint_args.build_only.is_some())?;
+    let challenge = connect_for_tx(args, &config, &mint_args.tx, signer, reporter).await?;
+    if let Some(path) = &mint_args.build_only {
+        let tx_data1 = challenge.build_mint_tx(config.mint_count).await?;
+        challenge.write_unsigned(Stage::Mint, tx_data1, path).await?;
+    } else if mint_args.tx.dry_run {
+        challenge.dry_run_mint(config.mint_count).await?;
+    } else {
+        challenge.mint(config.mint_count).await?;
     }
+    Ok(())
+}
+
+/// Transaction 2 only, against MINTCOINs minted by an earlier run.
+async fn get_flag(
+    args: &Args,
+    get_flag_args: &GetFlagArgs,
+    reporter: Arc<dyn Reporter>,
+) -> Result<(), ChallengeError> {
+    let config = args.config()?;
+    let signer = building_signer(args, get_flag_args.build_only.is_some())?;
+    let challenge = connect_for_tx(args, &config, &get_flag_args.tx, signer, reporter)
+        .await?
+        .with_flag_recipient(get_flag_args.flag_recipient)
+        .with_flag_event_watch(get_flag_args.watch_flag_event);
+    if get_flag_args.explain {
+        challenge.explain_coins(config.mint_count).await?;
+        return Ok(());
+    }
+    let mint_coins = select_mint_coins(challenge.owned_mint_coins().await?, config.mint_count as usize)?;
+    if let Some(path) = &get_flag_args.build_only {
+        let tx_data2 = challenge.build_get_flag_tx(&mint_coins, None).await?;
+        challenge.write_unsigned(Stage::GetFlag, tx_data2, path).await?;
+    } else if get_flag_args.tx.dry_run {
+        challenge.dry_run_get_flag(&mint_coins).await?;
+    } else {
+        challenge.get_flag(&mint_coins, None).await?;
+    }
+    Ok(())
+}
+
+/// Signs a transaction written by `--build-only` as its sender, and as its sponsor if it has one. Needs no network
+/// access.
+fn sign(args: &Args, sign_args: &SignArgs, reporter: &dyn Reporter) -> Result<(), ChallengeError> {
+    let tx_data: TransactionData = offline::read_bcs(&sign_args.tx_file)?;
+    let sender = tx_data.sender();
+    let signer = signer(args)?;
+    reporter.progress(&format!("Signing as {} with keys from {}", sender, signer.source()));
+    let signatures = signer.sign_transaction(&tx_data)?;
+    offline::write_bcs(&sign_args.out, &signatures)?;
+    reporter.progress(&format!("Signature written to {}", sign_args.out.display()));
+    Ok(())
+}
+
+/// Executes a transaction written by `--build-only` with the signature written by `sign`. Needs no keys.
+async fn submit(args: &Args, submit_args: &SubmitArgs, reporter: &dyn Reporter) -> Result<(), ChallengeError> {
+    let config = args.config()?;
+    let tx_data: TransactionData = offline::read_bcs(&submit_args.tx_file)?;
+    let signatures: Vec<Signature> = offline::read_bcs(&submit_args.sig_file)?;
+    let client = FailoverClient::connect(&config, reporter).await?;
+    let exec = ExecuteConfig {
+        max_attempts: submit_args.max_retries.saturating_add(1),
+        ..ExecuteConfig::default()
+    };
+    if let Some(confirm) = confirmation(args)
+        && !confirm(Stage::Submit, &tx_data)
+    {
+        return Err(ChallengeError::NotConfirmed(Stage::Submit));
+    }
+    offline::submit(&client, tx_data, signatures, &exec, reporter).await?;
+    Ok(())
+}
+
+async fn run_all(args: &Args, run_args: &RunArgs, reporter: Arc<dyn Reporter>) -> Result<(), ChallengeError> {
+    reporter.progress("Challenge 3: Starting multi-transaction flow");
+
+    let config = args.config()?;
+    let timeout = Duration::from_secs(run_args.timeout);
+    let started = Instant::now();
+    // Connecting counts towards --timeout too: a degraded node can hang it as much as any later step.
+    let challenge = tokio::time::timeout(timeout, connect_for_tx(args, &config, &run_args.tx, signer(args)?, reporter))
+        .await
+        .map_err(|_| ChallengeError::Timeout { step: Step::Connect, timeout })??
+        .with_flag_recipient(run_args.flag_recipient)
+        .with_flag_event_watch(run_args.watch_flag_event)
+        .with_coin_attempts(run_args.coin_attempts);
+    let connected = started.elapsed();
+    let runner = ChallengeRunner::new(challenge)
+        .with_config(&config)
+        .with_coin_timeout(Duration::from_secs(run_args.coin_timeout))
+        .with_single_tx(run_args.single_tx)
+        .with_skip_tx1(run_args.skip_tx1)
+        .with_timeout(Some(timeout))
+        .with_started(started);
+
+    if run_args.explain {
+        runner.challenge().explain_coins(config.mint_count).await?;
+        return Ok(());
+    }
+    if run_args.tx.dry_run {
+        if runner.uses_single_tx().await? {
+            runner.challenge().dry_run_single_tx(config.mint_count).await?;
+            return Ok(());
+        }
+        return dry_run(runner.challenge(), config.mint_count).await;
+    }
+    let outcome = runner.run().await?;
+    finish(runner.challenge().reporter(), &config, run_args, &outcome)?;
+    let mut timings = vec![(Step::Connect, connected)];
+    timings.extend(&outcome.timings);
+    runner.challenge().reporter().timings(&timings);
+    Ok(())
+}
+
+/// Writes the receipt if requested and reports the summary of a completed run.
+fn finish(
+    reporter: &dyn Reporter,
+    config: &ChallengeConfig,
+    run_args: &RunArgs,
+    outcome: &ChallengeOutcome,
+) -> Result<(), ChallengeError> {
+    if let Some(path) = &run_args.receipt {
+        RunReceipt {
+            sender: outcome.sender,
+            rpc_url: config.rpc_url.clone(),
+            tx1_digest: outcome.tx1_digest,
+            tx2_digest: outcome.tx2_digest,
+            tx1_gas_used: outcome.tx1_gas_used,
+            tx2_gas_used: outcome.tx2_gas_used,
+            flag_object: outcome.flag_object,
+            timestamp: Utc::now(),
+        }
+        .write(path)?;
+        reporter.progress(&format!("Receipt written to {}", path.display()));
+    }
+
+    reporter.finished(&RunSummary {
+        tx1_digest: outcome.tx1_digest,
+        tx2_digest: outcome.tx2_digest,
+        flag_obtained: outcome.flag_object.is_some(),
+        tx1_gas_used: outcome.tx1_gas_used,
+        tx2_gas_used: outcome.tx2_gas_used,
+        gas_used: outcome.gas_used(),
+        gas_cost: outcome.gas_cost(),
+    });
+
+    Ok(())
+}
+
+/// Simulates both transactions. Transaction 2 can only be simulated against MINTCOINs that already exist.
+async fn dry_run(challenge: &ChallengeClient, mint_count: u32) -> Result<(), ChallengeError> {
+    challenge.dry_run_mint(mint_count).await?;
+
+    let mint_coins = match select_mint_coins(challenge.owned_mint_coins().await?, mint_count as usize) {
+        Ok(mint_coins) => mint_coins,
+        Err(ChallengeError::InsufficientMintCoins { needed, found, .. }) => {
+            challenge.reporter().progress(&format!(
+                "Skipping transaction 2 dry run: it needs {} existing MINTCOINs, found {}",
+                needed, found
+            ));
+            return Ok(());
+        }
+        Err(error) => return Err(error),
+    };
+    challenge.dry_run_get_flag(&mint_coins).await?;
 
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,54 @@
+//! Air-gapped workflow: build a transaction online, sign it offline, submit it online again. Transactions and
+//! signatures travel between the machines as BCS files.
+
+use iota_sdk::{
+    rpc_types::IotaTransactionBlockResponse,
+    types::{
+        crypto::Signature,
+        transaction::{Transaction, TransactionData},
+    },
+};
+use serde::{Serialize, de::DeserializeOwned};
+use std::path::Path;
+
+use crate::{ChallengeError, ExecuteConfig, Reporter, Stage, TxExecutor, assert_success, execute_with_retry};
+
+/// Writes `value`, e.g. a `TransactionData` or its signatures, to `path` as BCS.
+pub fn write_bcs<T: Serialize>(path: &Path, value: &T) -> Result<(), ChallengeError> {
+    std::fs::write(path, bcs::to_bytes(value)?).map_err(|source| ChallengeError::FileWrite {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Reads a value written by `write_bcs`.
+pub fn read_bcs<T: DeserializeOwned>(path: &Path) -> Result<T, ChallengeError> {
+    let bytes = std::fs::read(path).map_err(|source| ChallengeError::FileRead {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    Ok(bcs::from_bytes(&bytes)?)
+}
+
+/// Executes `tx_data` with the detached `signatures` produced on another machine.
+pub async fn submit(
+    client: &impl TxExecutor,
+    tx_data: TransactionData,
+    signatures: Vec<Signature>,
+    exec: &ExecuteConfig,
+    reporter: &dyn Reporter,
+) -> Result<IotaTransactionBlockResponse, ChallengeError> {
+    reporter.progress("Executing the signed transaction");
+    let response = execute_with_retry(
+        client,
+        Transaction::from_data(tx_data, signatures),
+        exec.response_options(),
+        exec.wait,
+        exec.max_attempts,
+    )
+    .await?;
+    reporter.executed(Stage::Submit, &response);
+    assert_success(&response)?;
+    reporter.progress("Signed transaction succeeded");
+    Ok(response)
+}
@@ -0,0 +1,494 @@
+use iota_sdk::rpc_types::{
+    Coin, IotaEvent, IotaExecutionStatus, IotaTransactionBlockEffects, IotaTransactionBlockEffectsAPI,
+    IotaTransactionBlockResponse,
+};
+use iota_sdk::types::digests::TransactionDigest;
+use indicatif::ProgressBar;
+use serde_json::json;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{Level, debug, info, warn};
+
+use crate::{
+    Balances, ChallengeError, CoinSelection, DryRunResult, ObjectChangeSummary, RunSummary, Step, summarize_effects,
+};
+
+/// The transactions of the challenge flow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// Transaction 1: mint MINTCOINs.
+    Mint,
+    /// Transaction 2: merge, split and call `get_flag`.
+    GetFlag,
+    /// Both transactions batched into one under `--single-tx`.
+    Single,
+    /// A transaction built with `--build-only` and signed offline, run by `submit`.
+    Submit,
+}
+
+impl Stage {
+    /// Short machine-readable name used in JSON output.
+    pub fn id(self) -> &'static str {
+        match self {
+            Stage::Mint => "tx1",
+            Stage::GetFlag => "tx2",
+            Stage::Single => "tx",
+            Stage::Submit => "submit",
+        }
+    }
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stage::Mint => write!(f, "Transaction 1"),
+            Stage::GetFlag => write!(f, "Transaction 2"),
+            Stage::Single => write!(f, "Single transaction"),
+            Stage::Submit => write!(f, "Signed transaction"),
+        }
+    }
+}
+
+/// Where progress and results of a challenge run are written.
+pub trait Reporter: Send + Sync {
+    /// A high-level progress step.
+    fn progress(&self, message: &str);
+
+    /// Low-level detail such as individual PTB commands; hidden by default.
+    fn detail(&self, message: &str);
+
+    /// Still waiting, e.g. for coins to be indexed; called again with every update until `waited`.
+    fn waiting(&self, message: &str);
+
+    /// The wait reported by `waiting` is over, successfully or not.
+    fn waited(&self);
+
+    /// A transaction was executed.
+    fn executed(&self, stage: Stage, response: &IotaTransactionBlockResponse);
+
+    /// A transaction was simulated instead of executed.
+    fn simulated(&self, stage: Stage, result: &DryRunResult);
+
+    /// The objects an executed transaction created, mutated or deleted, under `--verbose-changes`.
+    fn object_changes(&self, stage: Stage, changes: &[ObjectChangeSummary]);
+
+    /// The full effects of an executed or simulated transaction, under `--verbose-effects`.
+    fn effects(&self, stage: Stage, effects: &IotaTransactionBlockEffects);
+
+    /// The `mintcoin` event emitted by the transaction claiming the flag arrived over the event subscription.
+    fn flag_event(&self, stage: Stage, event: &IotaEvent);
+
+    /// The full flow completed.
+    fn finished(&self, summary: &RunSummary);
+
+    /// How long each step of the completed flow took, in order.
+    fn timings(&self, timings: &[(Step, Duration)]);
+
+    /// Coin holdings were looked up by the `balance` subcommand.
+    fn balance(&self, balances: &Balances);
+
+    /// The coins a run would use, under `--explain`.
+    fn coin_selection(&self, selection: &CoinSelection);
+
+    /// One precondition checked by the `doctor` subcommand passed with `Ok(detail)` or failed.
+    fn check(&self, name: &str, outcome: Result<&str, &ChallengeError>);
+
+    /// The run failed; this is the last thing reported.
+    fn error(&self, error: &ChallengeError);
+}
+
+/// Human-readable output through `tracing`, so verbosity follows `RUST_LOG`.
+pub struct HumanReporter;
+
+/// The spinner shown on stderr during a wait; there is only one terminal, so at most one spinner.
+static SPINNER: Mutex<Option<ProgressBar>> = Mutex::new(None);
+
+impl Reporter for HumanReporter {
+    fn progress(&self, message: &str) {
+        info!("{}", message);
+    }
+
+    fn detail(&self, message: &str) {
+        debug!("{}", message);
+    }
+
+    /// Spins on stderr while waiting, unless `--quiet` (or `RUST_LOG`) hides progress; drawn only on a terminal.
+    fn waiting(&self, message: &str) {
+        if !tracing::enabled!(Level::INFO) {
+            return;
+        }
+        let mut spinner = SPINNER.lock().unwrap_or_else(|e| e.into_inner());
+        let spinner = spinner.get_or_insert_with(|| {
+            let spinner = ProgressBar::new_spinner();
+            spinner.enable_steady_tick(Duration::from_millis(100));
+            spinner
+        });
+        spinner.set_message(message.to_string());
+    }
+
+    fn waited(&self) {
+        if let Some(spinner) = SPINNER.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            spinner.finish_and_clear();
+        }
+    }
+
+    fn executed(&self, stage: Stage, response: &IotaTransactionBlockResponse) {
+        info!("{} executed, digest: {}", stage, response.digest);
+        if let Some(effects) = &response.effects {
+            info!("{}: {}", stage, summarize_effects(effects));
+        }
+    }
+
+    fn simulated(&self, stage: Stage, result: &DryRunResult) {
+        info!("{} projected: {}", stage, summarize_effects(&result.effects));
+        info!(
+            "{} projected gas used: {} (computation {}, storage {}, rebate {})",
+            stage,
+            result.gas_used,
+            result.gas_cost.computation_cost,
+            result.gas_cost.storage_cost,
+            result.gas_cost.storage_rebate,
+        );
+        match (result.move_abort(), result.abort()) {
+            (Some(abort), _) => warn!("{} would fail: {}", stage, abort),
+            (None, Some(error)) => warn!("{} would fail: {}", stage, error),
+            (None, None) => info!("{} would succeed", stage),
+        }
+    }
+
+    fn object_changes(&self, stage: Stage, changes: &[ObjectChangeSummary]) {
+        info!("{} object changes:\n{}", stage, object_change_table(changes));
+    }
+
+    fn effects(&self, stage: Stage, effects: &IotaTransactionBlockEffects) {
+        info!("{} effects: {:#?}", stage, effects);
+    }
+
+    fn flag_event(&self, stage: Stage, event: &IotaEvent) {
+        info!("{} emitted {} from {}: {}", stage, event.type_, event.sender, event.parsed_json);
+    }
+
+    fn finished(&self, summary: &RunSummary) {
+        let tx1 = summary
+            .tx1_digest
+            .map_or_else(|| "skipped".to_string(), |digest| digest.to_string());
+        info!(
+            "Done: transaction 1 {}, transaction 2 {}, flag obtained: {}, gas used: {}",
+            tx1, summary.tx2_digest, summary.flag_obtained, summary.gas_used
+        );
+        let tx1_gas = summary
+            .tx1_gas_used
+            .map_or_else(|| "skipped".to_string(), |gas_used| gas_used.to_string());
+        info!(
+            "Gas: transaction 1 {}, transaction 2 {}, total {} (computation {}, storage {}, rebate {})",
+            tx1_gas,
+            summary.tx2_gas_used,
+            summary.gas_used,
+            summary.gas_cost.computation_cost,
+            summary.gas_cost.storage_cost,
+            summary.gas_cost.storage_rebate,
+        );
+    }
+
+    fn timings(&self, timings: &[(Step, Duration)]) {
+        let timings: Vec<String> = timings
+            .iter()
+            .map(|(step, duration)| format!("{}: {}", step.id(), format_duration(*duration)))
+            .collect();
+        info!("Timings: {}", timings.join(", "));
+    }
+
+    fn balance(&self, balances: &Balances) {
+        info!("Address: {}", balances.address);
+        info!("IOTA gas balance: {}", balances.gas_balance);
+        info!(
+            "MINTCOINs: {} coins, {} in total",
+            balances.mint_coin_count, balances.mint_balance
+        );
+    }
+
+    fn coin_selection(&self, selection: &CoinSelection) {
+        info!(
+            "Gas coin: {} (balance {})",
+            selection.gas_coin.coin_object_id, selection.gas_coin.balance
+        );
+        if selection.mint_coins.is_empty() {
+            info!("MINTCOINs to merge: too few owned yet, transaction 2 merges the ones transaction 1 mints");
+        }
+        for coin in &selection.mint_coins {
+            info!("MINTCOIN to merge: {} (balance {})", coin.coin_object_id, coin.balance);
+        }
+        match selection.split {
+            Some(split) if split.remainder() == 0 && split.flag_count == 1 => {
+                info!("Split: none, the merged {} goes to get_flag as is", split.merged_balance)
+            }
+            Some(split) => info!(
+                "Split: {} x{} of the merged {} for get_flag, {} back to the sender",
+                split.flag_coin_value,
+                split.flag_count,
+                split.merged_balance,
+                split.remainder()
+            ),
+            None => {}
+        }
+    }
+
+    fn check(&self, name: &str, outcome: Result<&str, &ChallengeError>) {
+        match outcome {
+            Ok(detail) => info!("[pass] {}: {}", name, detail),
+            Err(error) => warn!("[FAIL] {}: {}", name, error_chain(error).join(": ")),
+        }
+    }
+
+    /// Only clears the spinner: the binary prints the error itself, so it shows even when logging is off.
+    fn error(&self, _error: &ChallengeError) {
+        // A wait cut short by a timeout or Ctrl-C never reported that it's over.
+        self.waited();
+    }
+}
+
+/// One JSON object per line on stdout, for scripts and CI.
+pub struct JsonReporter;
+
+impl JsonReporter {
+    fn emit(value: serde_json::Value) {
+        println!("{}", value);
+    }
+}
+
+impl Reporter for JsonReporter {
+    fn progress(&self, message: &str) {
+        Self::emit(json!({ "stage": "progress", "message": message }));
+    }
+
+    fn detail(&self, _message: &str) {}
+
+    fn waiting(&self, _message: &str) {}
+
+    fn waited(&self) {}
+
+    fn executed(&self, stage: Stage, response: &IotaTransactionBlockResponse) {
+        Self::emit(json!({
+            "stage": stage.id(),
+            "digest": response.digest.to_string(),
+            "status": response.effects.as_ref().map(|effects| status_str(effects.status())),
+            "effects_summary": response.effects.as_ref().map(summarize_effects),
+        }));
+    }
+
+    fn simulated(&self, stage: Stage, result: &DryRunResult) {
+        Self::emit(json!({
+            "stage": stage.id(),
+            "dry_run": true,
+            "status": status_str(&result.status),
+            "error": result.abort(),
+            "abort": result.move_abort(),
+            "gas_used": result.gas_used,
+        }));
+    }
+
+    fn object_changes(&self, stage: Stage, changes: &[ObjectChangeSummary]) {
+        Self::emit(json!({ "stage": stage.id(), "object_changes": changes }));
+    }
+
+    fn effects(&self, stage: Stage, effects: &IotaTransactionBlockEffects) {
+        Self::emit(json!({ "stage": stage.id(), "effects": effects }));
+    }
+
+    fn flag_event(&self, stage: Stage, event: &IotaEvent) {
+        Self::emit(json!({
+            "stage": stage.id(),
+            "flag_event": {
+                "type": event.type_.to_string(),
+                "sender": event.sender.to_string(),
+                "tx_digest": event.id.tx_digest.to_string(),
+                "fields": event.parsed_json,
+            },
+        }));
+    }
+
+    fn finished(&self, summary: &RunSummary) {
+        let mut value = json!(summary);
+        value["stage"] = json!("summary");
+        Self::emit(value);
+    }
+
+    fn timings(&self, timings: &[(Step, Duration)]) {
+        let millis: serde_json::Map<String, serde_json::Value> = timings
+            .iter()
+            .map(|(step, duration)| (step.id().to_string(), json!(duration.as_millis() as u64)))
+            .collect();
+        Self::emit(json!({ "stage": "timings", "timings": millis }));
+    }
+
+    fn balance(&self, balances: &Balances) {
+        let mut value = json!(balances);
+        value["stage"] = json!("balance");
+        Self::emit(value);
+    }
+
+    fn coin_selection(&self, selection: &CoinSelection) {
+        let coin = |coin: &Coin| json!({ "id": coin.coin_object_id.to_string(), "balance": coin.balance });
+        Self::emit(json!({
+            "stage": "coin_selection",
+            "gas_coin": coin(&selection.gas_coin),
+            "mint_coins": selection.mint_coins.iter().map(coin).collect::<Vec<_>>(),
+            "split": selection.split.map(|split| json!({
+                "merged_balance": split.merged_balance,
+                "flag_coin_value": split.flag_coin_value,
+                "flag_count": split.flag_count,
+                "remainder": split.remainder(),
+            })),
+        }));
+    }
+
+    fn check(&self, name: &str, outcome: Result<&str, &ChallengeError>) {
+        Self::emit(match outcome {
+            Ok(detail) => json!({ "stage": "check", "check": name, "passed": true, "detail": detail }),
+            Err(error) => json!({ "stage": "check", "check": name, "passed": false, "error": error_chain(error) }),
+        });
+    }
+
+    fn error(&self, error: &ChallengeError) {
+        let chain = error_chain(error);
+        let abort = match error {
+            ChallengeError::ExecutionFailed { abort, .. } => abort.as_ref(),
+            _ => None,
+        };
+        Self::emit(json!({
+            "stage": "error",
+            "error": chain[0],
+            "causes": chain[1..],
+            "abort": abort,
+            "exit_code": error.exit_code(),
+        }));
+    }
+}
+
+/// Passes everything on to another reporter while remembering which transactions were executed, so an interrupted
+/// run can still tell which digests it obtained.
+pub struct TrackingReporter {
+    inner: Arc<dyn Reporter>,
+    completed: Mutex<Vec<(Stage, TransactionDigest)>>,
+}
+
+impl TrackingReporter {
+    pub fn new(inner: Arc<dyn Reporter>) -> Self {
+        Self {
+            inner,
+            completed: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Transactions executed so far, in order.
+    pub fn completed(&self) -> Vec<(Stage, TransactionDigest)> {
+        self.completed.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+impl Reporter for TrackingReporter {
+    fn progress(&self, message: &str) {
+        self.inner.progress(message);
+    }
+
+    fn detail(&self, message: &str) {
+        self.inner.detail(message);
+    }
+
+    fn waiting(&self, message: &str) {
+        self.inner.waiting(message);
+    }
+
+    fn waited(&self) {
+        self.inner.waited();
+    }
+
+    fn executed(&self, stage: Stage, response: &IotaTransactionBlockResponse) {
+        self.completed
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push((stage, response.digest));
+        self.inner.executed(stage, response);
+    }
+
+    fn simulated(&self, stage: Stage, result: &DryRunResult) {
+        self.inner.simulated(stage, result);
+    }
+
+    fn object_changes(&self, stage: Stage, changes: &[ObjectChangeSummary]) {
+        self.inner.object_changes(stage, changes);
+    }
+
+    fn effects(&self, stage: Stage, effects: &IotaTransactionBlockEffects) {
+        self.inner.effects(stage, effects);
+    }
+
+    fn flag_event(&self, stage: Stage, event: &IotaEvent) {
+        self.inner.flag_event(stage, event);
+    }
+
+    fn finished(&self, summary: &RunSummary) {
+        self.inner.finished(summary);
+    }
+
+    fn timings(&self, timings: &[(Step, Duration)]) {
+        self.inner.timings(timings);
+    }
+
+    fn balance(&self, balances: &Balances) {
+        self.inner.balance(balances);
+    }
+
+    fn coin_selection(&self, selection: &CoinSelection) {
+        self.inner.coin_selection(selection);
+    }
+
+    fn check(&self, name: &str, outcome: Result<&str, &ChallengeError>) {
+        self.inner.check(name, outcome);
+    }
+
+    fn error(&self, error: &ChallengeError) {
+        self.inner.error(error);
+    }
+}
+
+/// Aligned `kind  object id  type` rows, one per object, under a header line.
+pub fn object_change_table(changes: &[ObjectChangeSummary]) -> String {
+    let mut table = format!("{:<11}  {:<66}  {}", "kind", "object id", "type");
+    for change in changes {
+        table.push_str(&format!(
+            "\n{:<11}  {:<66}  {}",
+            change.kind, change.object_id, change.object_type
+        ));
+    }
+    table
+}
+
+/// `320ms` below a second, `1.2s` from there on.
+pub fn format_duration(duration: Duration) -> String {
+    if duration < Duration::from_secs(1) {
+        format!("{}ms", duration.as_millis())
+    } else {
+        format!("{:.1}s", duration.as_secs_f64())
+    }
+}
+
+/// `error` followed by the message of each underlying cause, outermost first.
+pub fn error_chain(error: &ChallengeError) -> Vec<String> {
+    let mut chain = vec![error.to_string()];
+    let mut source = std::error::Error::source(error);
+    while let Some(cause) = source {
+        chain.push(cause.to_string());
+        source = cause.source();
+    }
+    chain
+}
+
+fn status_str(status: &IotaExecutionStatus) -> &'static str {
+    match status {
+        IotaExecutionStatus::Success => "success",
+        IotaExecutionStatus::Failure { .. } => "failure",
+    }
+}
+
@@ -0,0 +1,104 @@
+use iota_sdk::{
+    rpc_types::{IotaTransactionBlockResponse, IotaTransactionBlockResponseOptions},
+    types::{quorum_driver_types::ExecuteTransactionRequestType, transaction::Transaction},
+};
+use std::time::Duration;
+use tracing::warn;
+
+use crate::{ChallengeError, rpc::TxExecutor};
+
+/// How long `execute_transaction_block` waits before answering.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WaitStrategy {
+    /// Wait until the answering fullnode has executed the transaction locally.
+    #[default]
+    Local,
+    /// Wait only for the effects certificate from the validators.
+    Effects,
+}
+
+impl WaitStrategy {
+    pub fn request_type(self) -> ExecuteTransactionRequestType {
+        match self {
+            WaitStrategy::Local => ExecuteTransactionRequestType::WaitForLocalExecution,
+            WaitStrategy::Effects => ExecuteTransactionRequestType::WaitForEffectsCert,
+        }
+    }
+}
+
+/// Attempts made by `execute_with_retry` unless configured otherwise.
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 4;
+
+/// Delay before the first retry; doubled after every further failure, up to `MAX_BACKOFF`.
+pub const INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Longest delay between two attempts, however many are allowed.
+pub const MAX_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Substrings of RPC errors caused by the network or an overloaded node rather than the transaction.
+const TRANSIENT_MARKERS: &[&str] = &[
+    "timed out",
+    "timeout",
+    "connection",
+    "transport",
+    "502",
+    "503",
+    "504",
+    "restart needed",
+];
+
+/// Whether an RPC error is worth retrying. Move aborts never get here, as they come back as
+/// effects, and rejected signatures are reported as call errors, which are deterministic.
+pub fn is_transient(error: &iota_sdk::error::Error) -> bool {
+    match error {
+        iota_sdk::error::Error::RpcError(rpc) => {
+            let message = rpc.to_string().to_lowercase();
+            TRANSIENT_MARKERS.iter().any(|marker| message.contains(marker))
+        }
+        _ => false,
+    }
+}
+
+/// The delay after `backoff`: twice as long, but never more than `MAX_BACKOFF`.
+pub fn next_backoff(backoff: Duration) -> Duration {
+    backoff.saturating_mul(2).min(MAX_BACKOFF)
+}
+
+/// Runs `f` up to `attempts` times, backing off exponentially (100ms, 200ms, 400ms, ... up to `MAX_BACKOFF`) after
+/// transient RPC errors. Any other error is returned immediately. Connecting, the gas price query and execution
+/// all retry through here, so they share this one backoff policy.
+pub async fn retry<T, F, Fut>(attempts: u32, mut f: F) -> Result<T, iota_sdk::error::Error>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, iota_sdk::error::Error>>,
+{
+    let mut backoff = INITIAL_BACKOFF;
+    let mut attempt = 1;
+    loop {
+        match f().await {
+            Ok(value) => return Ok(value),
+            Err(error) if attempt < attempts && is_transient(&error) => {
+                warn!(
+                    "Attempt {}/{} failed: {}; retrying in {:?}",
+                    attempt, attempts, error, backoff
+                );
+                tokio::time::sleep(backoff).await;
+                backoff = next_backoff(backoff);
+                attempt += 1;
+            }
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+/// Submits `tx`, retrying transient RPC failures.
+pub async fn execute_with_retry(
+    client: &impl TxExecutor,
+    tx: Transaction,
+    opts: IotaTransactionBlockResponseOptions,
+    wait: WaitStrategy,
+    max_attempts: u32,
+) -> Result<IotaTransactionBlockResponse, ChallengeError> {
+    let response = retry(max_attempts, || client.execute_transaction_block(tx.clone(), opts.clone(), wait)).await?;
+    Ok(response)
+}
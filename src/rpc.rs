@@ -0,0 +1,274 @@
+use iota_sdk::{
+    IotaClient,
+    error::Error,
+    rpc_types::{Coin, IotaObjectDataOptions, IotaTransactionBlockEffects, IotaTransactionBlockResponse, IotaTransactionBlockResponseOptions},
+    types::{
+        base_types::{IotaAddress, ObjectID, ObjectRef},
+        transaction::{Transaction, TransactionData},
+    },
+};
+use std::fmt;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::sync::OnceCell;
+use tracing::warn;
+
+use crate::{
+    ChallengeConfig, ChallengeError, DEFAULT_MAX_ATTEMPTS, Reporter, WaitStrategy, client::rpc_client_builder, retry,
+    retry::is_transient,
+};
+
+/// Coin queries the challenge flow depends on. `IotaClient` implements it; tests can substitute a mock.
+pub trait CoinSource {
+    /// All of `owner`'s coins of `coin_type`, or of the gas coin when `None`.
+    fn get_coins(
+        &self,
+        owner: IotaAddress,
+        coin_type: Option<String>,
+    ) -> impl Future<Output = Result<Vec<Coin>, Error>> + Send;
+
+    fn get_reference_gas_price(&self) -> impl Future<Output = Result<u64, Error>> + Send;
+
+    /// Current reference of object `id`, read live rather than from the coin index; `None` once it's deleted.
+    fn latest_object_ref(&self, id: ObjectID) -> impl Future<Output = Result<Option<ObjectRef>, Error>> + Send;
+}
+
+/// Transaction submission and simulation. `IotaClient` implements it; tests can substitute a mock.
+pub trait TxExecutor {
+    fn execute_transaction_block(
+        &self,
+        tx: Transaction,
+        options: IotaTransactionBlockResponseOptions,
+        wait: WaitStrategy,
+    ) -> impl Future<Output = Result<IotaTransactionBlockResponse, Error>> + Send;
+
+    /// Effects of simulating `tx_data`, which is neither signed nor charged for.
+    fn dry_run_transaction_block(
+        &self,
+        tx_data: TransactionData,
+    ) -> impl Future<Output = Result<IotaTransactionBlockEffects, Error>> + Send;
+}
+
+impl CoinSource for IotaClient {
+    async fn get_coins(&self, owner: IotaAddress, coin_type: Option<String>) -> Result<Vec<Coin>, Error> {
+        // The node returns at most one page per request; a coin on a later page may be the one that's needed.
+        let mut coins = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = self.coin_read_api().get_coins(owner, coin_type.clone(), cursor, None).await?;
+            coins.extend(page.data);
+            match page.next_cursor {
+                Some(next) if page.has_next_page => cursor = Some(next),
+                _ => return Ok(coins),
+            }
+        }
+    }
+
+    async fn get_reference_gas_price(&self) -> Result<u64, Error> {
+        self.read_api().get_reference_gas_price().await
+    }
+
+    async fn latest_object_ref(&self, id: ObjectID) -> Result<Option<ObjectRef>, Error> {
+        let response = self
+            .read_api()
+            .get_object_with_options(id, IotaObjectDataOptions::new())
+            .await?;
+        Ok(response.data.map(|data| data.object_ref()))
+    }
+}
+
+impl TxExecutor for IotaClient {
+    async fn execute_transaction_block(
+        &self,
+        tx: Transaction,
+        options: IotaTransactionBlockResponseOptions,
+        wait: WaitStrategy,
+    ) -> Result<IotaTransactionBlockResponse, Error> {
+        self.quorum_driver_api()
+            .execute_transaction_block(tx, options, Some(wait.request_type()))
+            .await
+    }
+
+    async fn dry_run_transaction_block(&self, tx_data: TransactionData) -> Result<IotaTransactionBlockEffects, Error> {
+        let response = self.read_api().dry_run_transaction_block(tx_data).await?;
+        Ok(response.effects)
+    }
+}
+
+/// Substrings of RPC errors raised before the endpoint answered at all, such as a refused connection.
+const CONNECTION_MARKERS: &[&str] = &["connect", "networking"];
+
+/// Errors that tell whether the endpoint itself could not be reached, as opposed to it answering with an error.
+pub trait ConnectionFailure {
+    fn is_connection_failure(&self) -> bool;
+}
+
+impl ConnectionFailure for Error {
+    fn is_connection_failure(&self) -> bool {
+        let Error::RpcError(rpc) = self else {
+            return false;
+        };
+        let message = rpc.to_string().to_lowercase();
+        is_transient(self) || CONNECTION_MARKERS.iter().any(|marker| message.contains(marker))
+    }
+}
+
+impl ConnectionFailure for ChallengeError {
+    fn is_connection_failure(&self) -> bool {
+        matches!(self, ChallengeError::Rpc(error) if error.is_connection_failure())
+    }
+}
+
+/// One of the configured endpoints, connected on first use.
+struct Endpoint {
+    url: String,
+    client: OnceCell<IotaClient>,
+}
+
+/// The configured RPC endpoints behind one client. Every call goes to the current endpoint; when that one can't be
+/// reached, the call moves on to the next, which then stays current for the calls that follow.
+pub struct FailoverClient {
+    /// Only used to connect endpoints that aren't connected yet.
+    config: ChallengeConfig,
+    endpoints: Vec<Endpoint>,
+    current: AtomicUsize,
+}
+
+impl FailoverClient {
+    /// Connects to the first reachable endpoint of `config`, in order, retrying transient failures like
+    /// `connect_rpc` and giving up after `config.request_timeout` per endpoint. The other endpoints are connected
+    /// when failed over to.
+    pub async fn connect(config: &ChallengeConfig, reporter: &dyn Reporter) -> Result<Self, ChallengeError> {
+        let urls = config.rpc_urls().collect::<Vec<_>>().join(", ");
+        reporter.progress(&format!("Connecting to {}", urls));
+        let client = Self {
+            config: config.clone(),
+            endpoints: config
+                .rpc_urls()
+                .map(|url| Endpoint {
+                    url: url.to_string(),
+                    client: OnceCell::new(),
+                })
+                .collect(),
+            current: AtomicUsize::new(0),
+        };
+        let timeout = config.request_timeout * client.endpoints.len() as u32;
+        let connect = retry(DEFAULT_MAX_ATTEMPTS, || client.call(|_| async { Ok::<_, Error>(()) }));
+        tokio::time::timeout(timeout, connect)
+            .await
+            .map_err(|_| ChallengeError::ConnectTimeout { url: urls.clone(), timeout })?
+            .map_err(|source| ChallengeError::Connect { url: urls, source })?;
+        reporter.progress(&format!("Connected to {}", client.current_url()));
+        Ok(client)
+    }
+
+    /// The endpoint calls currently go to.
+    pub fn current(&self) -> &IotaClient {
+        self.endpoints[self.current.load(Ordering::Relaxed)]
+            .client
+            .get()
+            .expect("the current endpoint is connected")
+    }
+
+    pub fn current_url(&self) -> &str {
+        &self.endpoints[self.current.load(Ordering::Relaxed)].url
+    }
+
+    /// WebSocket endpoint subscriptions go to, if one is configured.
+    pub fn ws_url(&self) -> Option<&str> {
+        self.config.ws_url.as_deref()
+    }
+
+    /// Runs `f` on the current endpoint, and on the following ones in turn for as long as it fails with a
+    /// connection failure. The endpoint that answers becomes current.
+    pub async fn call<T, E, F, Fut>(&self, f: F) -> Result<T, E>
+    where
+        F: Fn(IotaClient) -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: From<Error> + ConnectionFailure + fmt::Display,
+    {
+        let start = self.current.load(Ordering::Relaxed);
+        let count = self.endpoints.len();
+        let mut last_error = None;
+        for offset in 0..count {
+            let index = (start + offset) % count;
+            let endpoint = &self.endpoints[index];
+            let client = endpoint
+                .client
+                .get_or_try_init(|| rpc_client_builder(&self.config).build(&endpoint.url))
+                .await;
+            let result = match client {
+                Ok(client) => f(client.clone()).await,
+                Err(error) => Err(E::from(error)),
+            };
+            match result {
+                Ok(value) => {
+                    self.current.store(index, Ordering::Relaxed);
+                    return Ok(value);
+                }
+                Err(error) if offset + 1 < count && error.is_connection_failure() => {
+                    warn!("RPC endpoint {} failed: {}; trying the next one", endpoint.url, error);
+                    last_error = Some(error);
+                }
+                Err(error) => return Err(error),
+            }
+        }
+        Err(last_error.expect("at least one endpoint is configured"))
+    }
+}
+
+impl From<IotaClient> for FailoverClient {
+    /// A single endpoint that is already connected.
+    fn from(client: IotaClient) -> Self {
+        Self {
+            config: ChallengeConfig::default(),
+            endpoints: vec![Endpoint {
+                url: String::new(),
+                client: OnceCell::new_with(Some(client)),
+            }],
+            current: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl CoinSource for FailoverClient {
+    async fn get_coins(&self, owner: IotaAddress, coin_type: Option<String>) -> Result<Vec<Coin>, Error> {
+        self.call(|client| {
+            let coin_type = coin_type.clone();
+            async move { CoinSource::get_coins(&client, owner, coin_type).await }
+        })
+        .await
+    }
+
+    async fn get_reference_gas_price(&self) -> Result<u64, Error> {
+        self.call(|client| async move { CoinSource::get_reference_gas_price(&client).await })
+            .await
+    }
+
+    async fn latest_object_ref(&self, id: ObjectID) -> Result<Option<ObjectRef>, Error> {
+        self.call(|client| async move { client.latest_object_ref(id).await }).await
+    }
+}
+
+impl TxExecutor for FailoverClient {
+    async fn execute_transaction_block(
+        &self,
+        tx: Transaction,
+        options: IotaTransactionBlockResponseOptions,
+        wait: WaitStrategy,
+    ) -> Result<IotaTransactionBlockResponse, Error> {
+        self.call(|client| {
+            let (tx, options) = (tx.clone(), options.clone());
+            async move { client.execute_transaction_block(tx, options, wait).await }
+        })
+        .await
+    }
+
+    async fn dry_run_transaction_block(&self, tx_data: TransactionData) -> Result<IotaTransactionBlockEffects, Error> {
+        self.call(|client| {
+            let tx_data = tx_data.clone();
+            async move { client.dry_run_transaction_block(tx_data).await }
+        })
+        .await
+    }
+}
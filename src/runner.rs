@@ -0,0 +1,345 @@
+//! The full challenge flow behind `run-all`, as a library call returning its results instead of printing them.
+
+use iota_sdk::{
+    rpc_types::{IotaTransactionBlockEffectsAPI, IotaTransactionBlockResponse},
+    types::{
+        base_types::{IotaAddress, ObjectID},
+        digests::TransactionDigest,
+        gas::GasCostSummary,
+    },
+};
+use serde::Serialize;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::{
+    ChallengeClient, ChallengeConfig, ChallengeError, DEFAULT_MINT_COUNT, HumanReporter, SenderSelection, Signer,
+    gas_object_ref_after, net_gas_used, select_mint_coins, total_gas_cost,
+};
+
+/// Seconds to wait for minted coins to be indexed unless configured otherwise.
+pub const DEFAULT_COIN_TIMEOUT_SECS: u64 = 30;
+
+/// Seconds `run-all` may take as a whole unless configured otherwise.
+pub const DEFAULT_RUN_TIMEOUT_SECS: u64 = 120;
+
+/// What a run is busy with, so a timeout can say where it got stuck and timings where the time went.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    /// Connecting and checking the deployment; timed by the caller, as it happens before there is a runner.
+    Connect,
+    /// Checking what the deployment supports and which MINTCOINs the sender already owns.
+    Preparing,
+    /// Building and executing transaction 1.
+    Mint,
+    /// Polling until the minted coins are indexed.
+    WaitForCoins,
+    /// Building and executing transaction 2.
+    GetFlag,
+    /// Building and executing the `--single-tx` transaction.
+    Single,
+}
+
+impl Step {
+    /// Short machine-readable name used in timings.
+    pub fn id(self) -> &'static str {
+        match self {
+            Step::Connect => "connect",
+            Step::Preparing => "prepare",
+            Step::Mint => "tx1",
+            Step::WaitForCoins => "sync",
+            Step::GetFlag => "tx2",
+            Step::Single => "tx",
+        }
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::Connect => write!(f, "connection"),
+            Step::Preparing => write!(f, "preparation"),
+            Step::Mint => write!(f, "transaction 1 execution"),
+            Step::WaitForCoins => write!(f, "the wait for MINTCOINs to be indexed"),
+            Step::GetFlag => write!(f, "transaction 2 execution"),
+            Step::Single => write!(f, "single transaction execution"),
+        }
+    }
+}
+
+/// Solves the challenge described by `config` in one call, signing as the `sender` account of `signer`, with
+/// the defaults of `run-all` and progress logged through `tracing`. Use `ChallengeRunner` for anything the
+/// defaults don't cover, such as a fixed gas budget or another `Reporter`.
+pub async fn run_challenge(
+    config: &ChallengeConfig,
+    signer: Signer,
+    sender: SenderSelection,
+) -> Result<ChallengeOutcome, ChallengeError> {
+    let challenge = ChallengeClient::connect(config, signer, sender, Arc::new(HumanReporter))
+        .await?
+        .with_flag_coin_value(config.flag_coin_value)
+        .with_flag_count(config.flag_count);
+    ChallengeRunner::new(challenge).with_config(config).run().await
+}
+
+/// What a completed run produced.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChallengeOutcome {
+    pub sender: IotaAddress,
+    /// `None` when transaction 1 was skipped, or batched with transaction 2 under `--single-tx`.
+    pub tx1_digest: Option<TransactionDigest>,
+    pub tx2_digest: TransactionDigest,
+    pub tx1_gas_used: Option<u64>,
+    pub tx2_gas_used: u64,
+    /// Computation, storage and rebate behind `tx1_gas_used`.
+    pub tx1_gas_cost: Option<GasCostSummary>,
+    pub tx2_gas_cost: GasCostSummary,
+    /// `None` when transaction 2 succeeded without creating a Flag the sender or `--flag-recipient` owns.
+    pub flag_object: Option<ObjectID>,
+    /// How long each step took, in order.
+    #[serde(skip)]
+    pub timings: Vec<(Step, Duration)>,
+}
+
+impl ChallengeOutcome {
+    /// Net gas used by both transactions together.
+    pub fn gas_used(&self) -> u64 {
+        self.tx1_gas_used.unwrap_or(0) + self.tx2_gas_used
+    }
+
+    /// Costs of both transactions together.
+    pub fn gas_cost(&self) -> GasCostSummary {
+        total_gas_cost(self.tx1_gas_cost.iter().chain([&self.tx2_gas_cost]))
+    }
+}
+
+/// Runs the whole challenge on a connected `ChallengeClient`: mint what is missing, wait for the coins, claim
+/// the flag. Progress goes to the client's `Reporter`, i.e. to `tracing` with a `HumanReporter`.
+pub struct ChallengeRunner {
+    challenge: ChallengeClient,
+    mint_count: u32,
+    coin_timeout: Duration,
+    single_tx: bool,
+    skip_tx1: bool,
+    timeout: Option<Duration>,
+    /// When the time `timeout` allows started running; `run` itself when `None`.
+    started: Option<Instant>,
+    steps: Mutex<StepLog>,
+}
+
+/// The step a run is in, since when, and how long the steps before it took.
+struct StepLog {
+    step: Step,
+    since: Instant,
+    done: Vec<(Step, Duration)>,
+}
+
+impl StepLog {
+    fn new() -> Self {
+        Self {
+            step: Step::Preparing,
+            since: Instant::now(),
+            done: Vec::new(),
+        }
+    }
+}
+
+impl ChallengeRunner {
+    pub fn new(challenge: ChallengeClient) -> Self {
+        Self {
+            challenge,
+            mint_count: DEFAULT_MINT_COUNT,
+            coin_timeout: Duration::from_secs(DEFAULT_COIN_TIMEOUT_SECS),
+            single_tx: false,
+            skip_tx1: false,
+            timeout: None,
+            started: None,
+            steps: Mutex::new(StepLog::new()),
+        }
+    }
+
+    /// Takes the mint count of `config`; its endpoint and deployment are the client's business.
+    pub fn with_config(mut self, config: &ChallengeConfig) -> Self {
+        self.mint_count = config.mint_count;
+        self
+    }
+
+    /// How long to wait for minted coins to be indexed before giving up.
+    pub fn with_coin_timeout(mut self, timeout: Duration) -> Self {
+        self.coin_timeout = timeout;
+        self
+    }
+
+    /// Mints and claims the flag in one transaction when the deployed `mint_coin` returns its coin.
+    pub fn with_single_tx(mut self, single_tx: bool) -> Self {
+        self.single_tx = single_tx;
+        self
+    }
+
+    /// Never mints; claims the flag with MINTCOINs the sender already owns.
+    pub fn with_skip_tx1(mut self, skip_tx1: bool) -> Self {
+        self.skip_tx1 = skip_tx1;
+        self
+    }
+
+    /// Abandons `run` with `ChallengeError::Timeout` once it took longer than `timeout` in total.
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Counts the `with_timeout` time from `started` rather than from the start of `run`, so it also covers what
+    /// happened before, such as connecting.
+    pub fn with_started(mut self, started: Instant) -> Self {
+        self.started = Some(started);
+        self
+    }
+
+    pub fn challenge(&self) -> &ChallengeClient {
+        &self.challenge
+    }
+
+    pub fn mint_count(&self) -> u32 {
+        self.mint_count
+    }
+
+    /// Whether the run batches everything into one transaction: `--single-tx` was asked for and the deployment
+    /// supports it. Reports the fallback to two transactions otherwise.
+    pub async fn uses_single_tx(&self) -> Result<bool, ChallengeError> {
+        if !self.single_tx {
+            return Ok(false);
+        }
+        if self.challenge.supports_single_tx().await? {
+            return Ok(true);
+        }
+        self.challenge
+            .reporter()
+            .progress("mint_coin does not return the minted coin; falling back to two transactions");
+        Ok(false)
+    }
+
+    pub async fn run(&self) -> Result<ChallengeOutcome, ChallengeError> {
+        *self.steps() = StepLog::new();
+        let Some(timeout) = self.timeout else {
+            return self.run_steps().await;
+        };
+        let remaining = self.started.map_or(timeout, |started| timeout.saturating_sub(started.elapsed()));
+        tokio::time::timeout(remaining, self.run_steps())
+            .await
+            .unwrap_or_else(|_| Err(ChallengeError::Timeout { step: self.step(), timeout }))
+    }
+
+    /// The step `run` is in, or stopped in.
+    pub fn step(&self) -> Step {
+        self.steps().step
+    }
+
+    /// How long each step of `run` took so far, in order, the current one included.
+    pub fn timings(&self) -> Vec<(Step, Duration)> {
+        let steps = self.steps();
+        let mut timings = steps.done.clone();
+        timings.push((steps.step, steps.since.elapsed()));
+        timings
+    }
+
+    fn enter(&self, step: Step) {
+        let mut steps = self.steps();
+        let finished = (steps.step, steps.since.elapsed());
+        steps.done.push(finished);
+        steps.step = step;
+        steps.since = Instant::now();
+    }
+
+    fn steps(&self) -> MutexGuard<'_, StepLog> {
+        self.steps.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    async fn run_steps(&self) -> Result<ChallengeOutcome, ChallengeError> {
+        let challenge = &self.challenge;
+        if self.uses_single_tx().await? {
+            self.enter(Step::Single);
+            let response = challenge.single_tx(self.mint_count).await?;
+            return Ok(self.outcome(None, &response));
+        }
+
+        let mint_count = self.mint_count as usize;
+        // Coins left over from an earlier partial run count towards the total, so reruns don't mint again.
+        let existing = challenge.owned_mint_coins().await?.len();
+        let (response1, effects) = if self.skip_tx1 {
+            challenge
+                .reporter()
+                .progress(&format!("Skipping transaction 1 (--skip-tx1), found {} MINTCOINs", existing));
+            (None, None)
+        } else if existing >= mint_count {
+            challenge.reporter().progress(&format!(
+                "Already holding {} MINTCOINs, skipping transaction 1: minting is unnecessary",
+                existing
+            ));
+            (None, None)
+        } else {
+            let missing = (mint_count - existing) as u32;
+            if existing > 0 {
+                challenge
+                    .reporter()
+                    .progress(&format!("Reusing {} existing MINTCOINs, minting {} more", existing, missing));
+            }
+            self.enter(Step::Mint);
+            let effects = challenge.watch_own_effects().await?;
+            (Some(challenge.mint(missing).await?), effects)
+        };
+
+        self.enter(Step::WaitForCoins);
+        let mint_coins = match (effects, &response1) {
+            (Some(effects), Some(response1)) => {
+                challenge
+                    .wait_for_mint_effects(effects, response1.digest, mint_count, self.coin_timeout)
+                    .await?
+            }
+            _ => challenge.wait_for_mint_coins(mint_count, self.coin_timeout).await?,
+        };
+        let mint_coins = select_mint_coins(mint_coins, mint_count)?;
+
+        // Transaction 1 already mutated our gas coin; its new version comes straight from the effects.
+        let gas_coin = match &response1 {
+            Some(response1) => Some(
+                response1
+                    .effects
+                    .as_ref()
+                    .ok_or(ChallengeError::MissingEffects(response1.digest))
+                    .and_then(gas_object_ref_after)?,
+            ),
+            None => None,
+        };
+        self.enter(Step::GetFlag);
+        let response2 = challenge.get_flag(&mint_coins, gas_coin).await?;
+
+        Ok(self.outcome(response1.as_ref(), &response2))
+    }
+
+    fn outcome(
+        &self,
+        response1: Option<&IotaTransactionBlockResponse>,
+        response2: &IotaTransactionBlockResponse,
+    ) -> ChallengeOutcome {
+        ChallengeOutcome {
+            sender: self.challenge.sender_address(),
+            tx1_digest: response1.map(|response| response.digest),
+            tx2_digest: response2.digest,
+            tx1_gas_used: response1.map(|response| net_gas_used(&gas_cost(response))),
+            tx2_gas_used: net_gas_used(&gas_cost(response2)),
+            tx1_gas_cost: response1.map(gas_cost),
+            tx2_gas_cost: gas_cost(response2),
+            flag_object: self.challenge.flag_object(response2).map(|(flag, _)| flag),
+            timings: self.timings(),
+        }
+    }
+}
+
+fn gas_cost(response: &IotaTransactionBlockResponse) -> GasCostSummary {
+    response
+        .effects
+        .as_ref()
+        .map(|effects| effects.gas_cost_summary().clone())
+        .unwrap_or_default()
+}
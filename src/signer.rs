@@ -0,0 +1,140 @@
+use iota_keys::keystore::{AccountKeystore, FileBasedKeystore, InMemKeystore};
+use iota_sdk::types::{
+    base_types::IotaAddress,
+    crypto::{IotaKeyPair, Signature, SignatureScheme},
+    transaction::{TransactionData, TransactionDataAPI},
+};
+use shared_crypto::intent::Intent;
+use std::path::{Path, PathBuf};
+
+use crate::ChallengeError;
+
+/// Environment variable holding a private key to sign with instead of the keystore file.
+pub const PRIVATE_KEY_ENV: &str = "IOTA_PRIVATE_KEY";
+
+/// Environment variable overriding the default keystore file location.
+pub const KEYSTORE_PATH_ENV: &str = "IOTA_KEYSTORE_PATH";
+
+/// Key schemes whose keys can sign the challenge transactions.
+pub const SUPPORTED_SCHEMES: [SignatureScheme; 3] =
+    [SignatureScheme::ED25519, SignatureScheme::Secp256k1, SignatureScheme::Secp256r1];
+
+/// Where the keys that sign the challenge transactions come from.
+pub enum Signer {
+    /// A keystore file, usually `~/.iota/iota_config/iota.keystore`.
+    File(FileBasedKeystore),
+    /// A single key held in memory, e.g. from `IOTA_PRIVATE_KEY`.
+    InMemory(InMemKeystore),
+    /// Just an address, for building transactions with `--build-only` on a machine without its key.
+    WatchOnly(IotaAddress),
+    /// Several keystores, e.g. from repeated `--keystore`; each address signs with the one holding its key.
+    Many(Vec<Signer>),
+}
+
+impl Signer {
+    pub fn from_file(path: &Path) -> Result<Self, ChallengeError> {
+        // FileBasedKeystore would silently start an empty keystore at a mistyped path.
+        if !path.exists() {
+            return Err(ChallengeError::KeystoreNotFound { path: path.to_path_buf() });
+        }
+        let keystore = FileBasedKeystore::new(&path.to_path_buf()).map_err(ChallengeError::Keystore)?;
+        Ok(Signer::File(keystore))
+    }
+
+    /// Loads every keystore in `paths`, merging their addresses; a single path is the same as `from_file`.
+    pub fn from_files(paths: &[PathBuf]) -> Result<Self, ChallengeError> {
+        match paths {
+            [path] => Self::from_file(path),
+            paths => Ok(Signer::Many(paths.iter().map(|path| Self::from_file(path)).collect::<Result<_, _>>()?)),
+        }
+    }
+
+    /// Builds an in-memory keystore from a bech32 (`iotaprivkey1...`) or base64 (flag || key) private key.
+    pub fn from_private_key(key: &str) -> Result<Self, ChallengeError> {
+        let key = key.trim();
+        let keypair = IotaKeyPair::decode(key)
+            .ok()
+            .or_else(|| IotaKeyPair::decode_base64(key).ok())
+            .ok_or(ChallengeError::InvalidPrivateKey)?;
+        let mut keystore = InMemKeystore::default();
+        keystore.add_key(None, keypair).map_err(ChallengeError::Keystore)?;
+        Ok(Signer::InMemory(keystore))
+    }
+
+    /// Human-readable description of where the keys come from.
+    pub fn source(&self) -> &'static str {
+        match self {
+            Signer::File(_) => "keystore file",
+            Signer::InMemory(_) => PRIVATE_KEY_ENV,
+            Signer::WatchOnly(_) => "--sender (watch-only)",
+            Signer::Many(_) => "keystore files",
+        }
+    }
+
+    pub fn addresses(&self) -> Vec<IotaAddress> {
+        match self {
+            Signer::File(keystore) => keystore.addresses(),
+            Signer::InMemory(keystore) => keystore.addresses(),
+            Signer::WatchOnly(address) => vec![*address],
+            Signer::Many(signers) => {
+                let mut addresses = Vec::new();
+                for address in signers.iter().flat_map(Signer::addresses) {
+                    if !addresses.contains(&address) {
+                        addresses.push(address);
+                    }
+                }
+                addresses
+            }
+        }
+    }
+
+    /// The signer holding the key for `address`: the first keystore that has it, or `self` when there is one.
+    fn holder(&self, address: &IotaAddress) -> Result<&Signer, ChallengeError> {
+        match self {
+            Signer::Many(signers) => signers
+                .iter()
+                .find(|signer| signer.addresses().contains(address))
+                .ok_or_else(|| ChallengeError::Signing(format!("no keystore holds a key for {}", address))),
+            signer => Ok(signer),
+        }
+    }
+
+    /// Scheme of the key held for `address`.
+    pub fn scheme(&self, address: &IotaAddress) -> Result<SignatureScheme, ChallengeError> {
+        let holder = self.holder(address)?;
+        let key = match holder {
+            Signer::File(keystore) => keystore.get_key(address),
+            Signer::InMemory(keystore) => keystore.get_key(address),
+            Signer::WatchOnly(address) => return Err(ChallengeError::WatchOnly(*address)),
+            Signer::Many(_) => return holder.scheme(address),
+        };
+        let key = key.map_err(|e| ChallengeError::Signing(e.to_string()))?;
+        Ok(key.public().scheme())
+    }
+
+    /// Every signature `tx_data` needs: the sender's, then the gas owner's when a sponsor pays for gas.
+    pub fn sign_transaction(&self, tx_data: &TransactionData) -> Result<Vec<Signature>, ChallengeError> {
+        let mut signatures = vec![self.sign(&tx_data.sender(), tx_data)?];
+        if tx_data.gas_owner() != tx_data.sender() {
+            signatures.push(self.sign(&tx_data.gas_owner(), tx_data)?);
+        }
+        Ok(signatures)
+    }
+
+    /// Signs `tx_data` as `address` with the transaction intent, using whichever of the `SUPPORTED_SCHEMES` its
+    /// key has.
+    pub fn sign(&self, address: &IotaAddress, tx_data: &TransactionData) -> Result<Signature, ChallengeError> {
+        let scheme = self.scheme(address)?;
+        if !SUPPORTED_SCHEMES.contains(&scheme) {
+            return Err(ChallengeError::UnsupportedScheme(scheme));
+        }
+        let holder = self.holder(address)?;
+        let signature = match holder {
+            Signer::File(keystore) => keystore.sign_secure(address, tx_data, Intent::iota_transaction()),
+            Signer::InMemory(keystore) => keystore.sign_secure(address, tx_data, Intent::iota_transaction()),
+            Signer::WatchOnly(address) => return Err(ChallengeError::WatchOnly(*address)),
+            Signer::Many(_) => return holder.sign(address, tx_data),
+        };
+        signature.map_err(|e| ChallengeError::Signing(e.to_string()))
+    }
+}
@@ -0,0 +1,62 @@
+//! Move abort codes and locations are recovered from execution error strings.
+
+use iota_challenge3_example::{MoveAbort, move_abort_code, parse_move_abort};
+use iota_sdk::rpc_types::IotaExecutionStatus;
+
+#[test]
+fn abort_code_of_a_move_abort() {
+    let error = "MoveAbort(MoveLocation { module: ModuleId { address: c6f00a2b5ec2d161442b305dcb307ba914e20c5268ec931bd14d7ea3454b262b, name: Identifier(\"mintcoin\") }, function: 2, instruction: 12, function_name: Some(\"get_flag\") }, 3) in command 3";
+    assert_eq!(move_abort_code(error), Some(3));
+}
+
+#[test]
+fn move_abort_names_where_it_happened() {
+    let error = "MoveAbort(MoveLocation { module: ModuleId { address: c6f00a2b5ec2d161442b305dcb307ba914e20c5268ec931bd14d7ea3454b262b, name: Identifier(\"mintcoin\") }, function: 2, instruction: 12, function_name: Some(\"get_flag\") }, 3) in command 3";
+    let abort = parse_move_abort(error).unwrap();
+    assert_eq!(
+        abort,
+        MoveAbort {
+            module: Some("mintcoin".to_string()),
+            function: Some("get_flag".to_string()),
+            code: 3,
+        }
+    );
+    assert_eq!(
+        abort.to_string(),
+        "get_flag aborted in mintcoin with code 3 (likely wrong coin value; see --flag-coin-value)"
+    );
+}
+
+#[test]
+fn abort_without_a_known_cause_has_no_hint() {
+    let error = "MoveAbort(MoveLocation { module: ModuleId { address: 2, name: Identifier(\"balance\") }, function: 5, instruction: 10, function_name: Some(\"split\") }, 2) in command 1";
+    assert_eq!(parse_move_abort(error).unwrap().to_string(), "split aborted in balance with code 2");
+}
+
+#[test]
+fn other_failures_have_no_abort_code() {
+    assert_eq!(move_abort_code("InsufficientGas"), None);
+    assert_eq!(move_abort_code("CommandArgumentError { arg_idx: 1, kind: TypeMismatch } in command 2"), None);
+}
+
+#[test]
+fn malformed_aborts_have_no_abort_code() {
+    // Unbalanced brackets, a non-numeric code and a missing code.
+    assert_eq!(move_abort_code("MoveAbort(MoveLocation { module: ModuleId { , 3"), None);
+    assert_eq!(move_abort_code("MoveAbort(MoveLocation { function: 2 }, three) in command 0"), None);
+    assert_eq!(move_abort_code("MoveAbort()"), None);
+    assert_eq!(move_abort_code("MoveAbort("), None);
+}
+
+#[test]
+fn abort_is_read_from_a_failed_status() {
+    let failure = IotaExecutionStatus::Failure {
+        error: "MoveAbort(MoveLocation { module: ModuleId { address: a1, name: Identifier(\"mintcoin\") }, function: 2, instruction: 12, function_name: Some(\"get_flag\") }, 0) in command 3".to_string(),
+    };
+    let abort = MoveAbort::from_status(&failure).unwrap();
+    assert_eq!((abort.function.as_deref(), abort.code), (Some("get_flag"), 0));
+
+    assert_eq!(MoveAbort::from_status(&IotaExecutionStatus::Success), None);
+    let out_of_gas = IotaExecutionStatus::Failure { error: "InsufficientGas".to_string() };
+    assert_eq!(MoveAbort::from_status(&out_of_gas), None);
+}
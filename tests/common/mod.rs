@@ -0,0 +1,61 @@
+//! Fixtures shared by the integration tests.
+#![allow(dead_code)]
+
+use iota_challenge3_example::Deployment;
+use iota_sdk::{
+    rpc_types::Coin,
+    types::{
+        base_types::{IotaAddress, ObjectDigest, ObjectID, SequenceNumber},
+        digests::TransactionDigest,
+    },
+};
+use serde_json::{Value, json};
+
+/// Base58 of 32 zero bytes, a valid object or transaction digest.
+pub const DIGEST: &str = "11111111111111111111111111111111";
+
+pub fn object_id(n: u8) -> ObjectID {
+    ObjectID::from_single_byte(n)
+}
+
+pub fn deployment() -> Deployment {
+    Deployment {
+        package_id: object_id(0xa1),
+        treasury_cap_id: object_id(0xa2),
+        shared_counter_id: object_id(0xa3),
+        ..Deployment::default()
+    }
+}
+
+pub fn coin(coin_type: &str, id: u8, balance: u64) -> Coin {
+    Coin {
+        coin_type: coin_type.to_string(),
+        coin_object_id: object_id(id),
+        version: SequenceNumber::from_u64(1),
+        digest: ObjectDigest::random(),
+        balance,
+        previous_transaction: TransactionDigest::random(),
+    }
+}
+
+/// JSON effects of a transaction by `sender` with the given `status` and gas costs, paid for with coin 1.
+pub fn effects(sender: IotaAddress, status: Value, computation_cost: u64, storage_cost: u64, storage_rebate: u64) -> Value {
+    json!({
+        "messageVersion": "v1",
+        "status": status,
+        "executedEpoch": "0",
+        "gasUsed": {
+            "computationCost": computation_cost.to_string(),
+            "computationCostBurned": computation_cost.to_string(),
+            "storageCost": storage_cost.to_string(),
+            "storageRebate": storage_rebate.to_string(),
+            "nonRefundableStorageFee": "0",
+        },
+        "transactionDigest": DIGEST,
+        "gasObject": {
+            "owner": { "AddressOwner": sender.to_string() },
+            "reference": { "objectId": object_id(1).to_string(), "version": "2", "digest": DIGEST },
+        },
+        "dependencies": [],
+    })
+}
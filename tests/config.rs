@@ -0,0 +1,154 @@
+//! Config files are validated like the equivalent command-line flags.
+
+use iota_challenge3_example::{
+    ChallengeConfig, ChallengeError, Deployment, FileConfig, Network, RpcHeader,
+    config::{DeploymentManifest, ws_url_for},
+};
+use iota_sdk::types::base_types::ObjectID;
+use std::path::PathBuf;
+
+/// Writes `contents` to a file in the temp directory unique to this test.
+fn config_file(name: &str, contents: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("challenge-{}-{}.toml", name, std::process::id()));
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+#[test]
+fn rpc_url_must_be_http() {
+    let path = config_file("bad-rpc-url", "rpc_url = \"htps//api.testnet.iota.cafe\"\n");
+    let error = FileConfig::load(&path).unwrap_err();
+    assert!(matches!(error, ChallengeError::InvalidConfig { reason, .. } if reason.starts_with("rpc_url")));
+}
+
+#[test]
+fn valid_rpc_url_is_kept() {
+    let path = config_file("good-rpc-url", "rpc_url = \"https://api.testnet.iota.cafe\"\n");
+    let config = FileConfig::load(&path).unwrap();
+    assert_eq!(config.rpc_url.as_deref(), Some("https://api.testnet.iota.cafe"));
+}
+
+#[test]
+fn rpc_header_is_split_at_the_first_colon() {
+    let header: RpcHeader = "Authorization: Bearer a:b".parse().unwrap();
+    assert_eq!(header.name, "Authorization");
+    assert_eq!(header.value, "Bearer a:b");
+}
+
+#[test]
+fn malformed_rpc_headers_are_rejected() {
+    assert!("X-Api-Key".parse::<RpcHeader>().is_err());
+    assert!("X Api Key: secret".parse::<RpcHeader>().is_err());
+    assert!(": secret".parse::<RpcHeader>().is_err());
+}
+
+#[test]
+fn rpc_headers_are_read_from_the_config_file() {
+    let path = config_file("rpc-headers", "rpc_headers = [\"X-Api-Key: secret\"]\n");
+    let config = FileConfig::load(&path).unwrap();
+    assert_eq!(config.rpc_headers, Some(vec!["X-Api-Key: secret".parse().unwrap()]));
+
+    let path = config_file("bad-rpc-headers", "rpc_headers = [\"X-Api-Key\"]\n");
+    assert!(matches!(FileConfig::load(&path).unwrap_err(), ChallengeError::ConfigParse { .. }));
+}
+
+#[test]
+fn flag_coin_value_must_be_positive() {
+    let path = config_file("zero-flag-coin-value", "flag_coin_value = 0\n");
+    let error = FileConfig::load(&path).unwrap_err();
+    assert!(matches!(error, ChallengeError::InvalidConfig { reason, .. } if reason.starts_with("flag_coin_value")));
+}
+
+#[test]
+fn request_timeout_must_be_positive() {
+    let path = config_file("zero-timeout", "request_timeout = 0\n");
+    let error = FileConfig::load(&path).unwrap_err();
+    assert!(matches!(error, ChallengeError::InvalidConfig { reason, .. } if reason.starts_with("request_timeout")));
+}
+
+#[test]
+fn network_presets_the_endpoints() {
+    let path = config_file("localnet", "network = \"localnet\"\n");
+    let config = ChallengeConfig::load(Some(&path)).unwrap();
+    assert_eq!(config.rpc_url, "http://127.0.0.1:9000");
+    assert_eq!(config.faucet_url.as_deref(), Some("http://127.0.0.1:9123/gas"));
+}
+
+#[test]
+fn explicit_rpc_url_wins_over_the_network() {
+    let path = config_file("devnet-custom-rpc", "network = \"devnet\"\nrpc_url = \"http://10.0.0.2:9000\"\n");
+    let config = ChallengeConfig::load(Some(&path)).unwrap();
+    assert_eq!(config.rpc_url, "http://10.0.0.2:9000");
+    assert_eq!(config.faucet_url.as_deref(), Network::Devnet.faucet_url());
+}
+
+#[test]
+fn default_network_requests_no_funds() {
+    assert_eq!(ChallengeConfig::default().faucet_url, None);
+    assert!("moonnet".parse::<Network>().is_err());
+}
+
+#[test]
+fn explicit_flag_consumes_coin_is_kept_apart_from_unset() {
+    assert_eq!(ChallengeConfig::default().deployment.get_flag_consumes_coin, None);
+
+    let path = config_file("flag-keeps-coin", "flag_consumes_coin = false\n");
+    let config = ChallengeConfig::load(Some(&path)).unwrap();
+    assert_eq!(config.deployment.get_flag_consumes_coin, Some(false));
+}
+
+#[test]
+fn ws_url_must_be_websocket() {
+    let path = config_file("bad-ws-url", "ws_url = \"https://api.testnet.iota.cafe\"\n");
+    let error = FileConfig::load(&path).unwrap_err();
+    assert!(matches!(error, ChallengeError::InvalidConfig { reason, .. } if reason.starts_with("ws_url")));
+}
+
+#[test]
+fn ws_url_defaults_to_the_rpc_url_over_websocket() {
+    assert_eq!(ws_url_for("https://api.testnet.iota.cafe"), "wss://api.testnet.iota.cafe");
+    assert_eq!(ws_url_for("http://127.0.0.1:9000"), "ws://127.0.0.1:9000");
+}
+
+#[test]
+fn coin_module_must_be_an_identifier() {
+    let path = config_file("bad-coin-module", "coin_module = \"mint-coin\"\n");
+    let error = FileConfig::load(&path).unwrap_err();
+    assert!(matches!(error, ChallengeError::InvalidConfig { reason, .. } if reason.starts_with("coin_module")));
+}
+
+#[test]
+fn json_manifest_sets_the_deployment() {
+    let path = std::env::temp_dir().join(format!("challenge-manifest-{}.json", std::process::id()));
+    std::fs::write(&path, r#"{ "package_id": "0x1", "treasury_cap_id": "0x2", "shared_counter_id": "0x3" }"#).unwrap();
+    let mut deployment = Deployment::default();
+
+    DeploymentManifest::load(&path).unwrap().apply_to(&mut deployment, &path).unwrap();
+
+    assert_eq!(deployment.package_id, ObjectID::from_single_byte(1));
+    assert_eq!(deployment.treasury_cap_id, ObjectID::from_single_byte(2));
+    assert_eq!(deployment.shared_counter_id, ObjectID::from_single_byte(3));
+}
+
+#[test]
+fn manifest_ids_can_come_from_the_command_line() {
+    let path = config_file("partial-manifest", "package_id = \"0x1\"\ntreasury_cap_id = \"0x2\"\n");
+    let manifest = DeploymentManifest::load(&path).unwrap();
+    let mut deployment = Deployment::default();
+
+    let error = DeploymentManifest::load(&path)
+        .unwrap()
+        .apply_to(&mut deployment, &path)
+        .unwrap_err();
+    assert!(matches!(error, ChallengeError::InvalidManifest { reason, .. } if reason.contains("--shared-counter-id")));
+
+    let flags = DeploymentManifest {
+        package_id: Some(ObjectID::from_single_byte(9)),
+        shared_counter_id: Some(ObjectID::from_single_byte(3)),
+        ..DeploymentManifest::default()
+    };
+    manifest.overridden_by(flags).apply_to(&mut deployment, &path).unwrap();
+    assert_eq!(deployment.package_id, ObjectID::from_single_byte(9));
+    assert_eq!(deployment.treasury_cap_id, ObjectID::from_single_byte(2));
+    assert_eq!(deployment.shared_counter_id, ObjectID::from_single_byte(3));
+}
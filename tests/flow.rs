@@ -0,0 +1,507 @@
+//! Drives `ChallengeClient` against a mocked JSON-RPC node.
+
+mod common;
+
+use common::{DIGEST, coin, deployment, effects, object_id};
+use iota_challenge3_example::{
+    ChallengeClient, ChallengeConfig, ChallengeError, ChallengeRunner, HumanReporter, RpcHeader, SenderSelection,
+    Signer, Step, decode_counter,
+};
+use iota_keys::keystore::InMemKeystore;
+use iota_sdk::{
+    rpc_types::{IotaRawData, IotaRawMoveObject},
+    types::{
+        base_types::{IotaAddress, ObjectID, SequenceNumber},
+        transaction::{Argument, CallArg, Command, TransactionDataAPI, TransactionKind},
+    },
+};
+use serde_json::{Value, json};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate, matchers::method};
+
+/// Answers every JSON-RPC call with `handler(method, params)`, echoing the request id.
+struct RpcResponder<F>(F);
+
+impl<F> Respond for RpcResponder<F>
+where
+    F: Fn(&str, &Value) -> Value + Send + Sync + 'static,
+{
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let body: Value = serde_json::from_slice(&request.body).unwrap();
+        let method = body["method"].as_str().unwrap();
+        let result = (self.0)(method, &body["params"]);
+        ResponseTemplate::new(200).set_body_json(json!({ "jsonrpc": "2.0", "id": body["id"], "result": result }))
+    }
+}
+
+/// A node holding one gas coin and `mint_coin_count` MINTCOINs for `sender`, listed two per page, whose shared
+/// counter goes up by one with every executed transaction, which is checkpointed right away.
+fn node(sender: IotaAddress, mint_coin_count: u8) -> impl Fn(&str, &Value) -> Value + Send + Sync + 'static {
+    let package = deployment().package_id;
+    let mint_coin_type = deployment().mint_coin_type();
+    let counter = AtomicU64::new(0);
+    move |method, params| match method {
+        "rpc.discover" => json!({
+            "openrpc": "1.2.6",
+            "info": { "title": "IOTA JSON-RPC", "version": "1.0.0" },
+            "methods": [],
+        }),
+        "iota_getChainIdentifier" => json!("mock"),
+        "iota_getNormalizedMoveModulesByPackage" => json!({ "mintcoin": mintcoin_module(package) }),
+        "iota_getObject" if params[0] == json!(package.to_string()) => json!({
+            "data": { "objectId": params[0], "version": "1", "digest": DIGEST, "type": "package" }
+        }),
+        m if m.ends_with("getReferenceGasPrice") => json!("1000"),
+        "iotax_getCoins" => {
+            let coins = if params[1].is_null() {
+                vec![coin("0x2::iota::IOTA", 1, 1_000_000_000)]
+            } else {
+                (0..mint_coin_count).map(|i| coin(&mint_coin_type, 0x10 + i, 2)).collect()
+            };
+            // The cursor is the last coin of the previous page.
+            let start = coins
+                .iter()
+                .position(|coin| params[2] == json!(coin.coin_object_id.to_string()))
+                .map_or(0, |last| last + 1);
+            let page = &coins[start..coins.len().min(start + 2)];
+            let has_next_page = start + page.len() < coins.len();
+            let data: Vec<Value> = page.iter().map(|coin| serde_json::to_value(coin).unwrap()).collect();
+            let next_cursor = page.last().map(|coin| coin.coin_object_id.to_string());
+            json!({ "data": data, "nextCursor": next_cursor, "hasNextPage": has_next_page })
+        }
+        "iota_getObject" if params[1]["showBcs"] == json!(true) => {
+            let value = counter.load(Ordering::SeqCst);
+            let bcs = IotaRawData::MoveObject(IotaRawMoveObject {
+                type_: format!("{}::mintcoin::Counter", package).parse().unwrap(),
+                has_public_transfer: false,
+                version: SequenceNumber::from_u64(value + 1),
+                bcs_bytes: counter_bcs(value),
+            });
+            json!({
+                "data": {
+                    "objectId": params[0],
+                    "version": (value + 1).to_string(),
+                    "digest": DIGEST,
+                    "bcs": bcs,
+                }
+            })
+        }
+        "iota_getObject" => json!({
+            "data": {
+                "objectId": params[0],
+                "version": "1",
+                "digest": DIGEST,
+                "owner": { "Shared": { "initial_shared_version": 1 } },
+            }
+        }),
+        "iota_getTransactionBlock" => json!({ "digest": params[0], "checkpoint": "5" }),
+        "iota_executeTransactionBlock" => {
+            counter.fetch_add(1, Ordering::SeqCst);
+            json!({
+                "digest": DIGEST,
+                "effects": minted(effects(sender, json!({ "status": "success" }), 1000, 2000, 500), sender),
+                "objectChanges": [{
+                    "type": "created",
+                    "sender": sender.to_string(),
+                    "owner": { "AddressOwner": sender.to_string() },
+                    "objectType": format!("{}::mintcoin::Flag", package),
+                    "objectId": object_id(0xf1).to_string(),
+                    "version": "2",
+                    "digest": DIGEST,
+                }],
+                "confirmedLocalExecution": true,
+            })
+        }
+        other => panic!("unexpected RPC call {other}"),
+    }
+}
+
+/// The normalized `mintcoin` module of the test deployment, exposing `mint_coin` and `get_flag` with neither
+/// returning anything.
+fn mintcoin_module(package: ObjectID) -> Value {
+    let function = json!({
+        "visibility": "Public",
+        "isEntry": true,
+        "typeParameters": [],
+        "parameters": [],
+        "return": [],
+    });
+    json!({
+        "fileFormatVersion": 6,
+        "address": package.to_string(),
+        "name": "mintcoin",
+        "friends": [],
+        "structs": {},
+        "exposedFunctions": { "mint_coin": function, "get_flag": function },
+    })
+}
+
+/// BCS contents of a shared counter holding `value`.
+fn counter_bcs(value: u64) -> Vec<u8> {
+    bcs::to_bytes(&(deployment().shared_counter_id, value)).unwrap()
+}
+
+/// Adds three coins created for `sender`, objects 0x20 to 0x22, to `effects`.
+fn minted(mut effects: Value, sender: IotaAddress) -> Value {
+    effects["created"] = (0x20..0x23)
+        .map(|id| {
+            json!({
+                "owner": { "AddressOwner": sender.to_string() },
+                "reference": { "objectId": object_id(id).to_string(), "version": "2", "digest": DIGEST },
+            })
+        })
+        .collect();
+    effects
+}
+
+/// Settings pointing at the mocked node and the test deployment.
+fn config(server: &MockServer) -> ChallengeConfig {
+    ChallengeConfig {
+        rpc_url: server.uri(),
+        deployment: deployment(),
+        ..ChallengeConfig::default()
+    }
+}
+
+async fn start(mint_coin_count: u8) -> (MockServer, ChallengeClient) {
+    let signer = Signer::InMemory(InMemKeystore::new_insecure_for_tests(1));
+    let sender = signer.addresses()[0];
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(RpcResponder(node(sender, mint_coin_count)))
+        .mount(&server)
+        .await;
+
+    let client = ChallengeClient::connect(&config(&server), signer, SenderSelection::First, Arc::new(HumanReporter))
+        .await
+        .unwrap()
+        // A fixed budget skips the dry-run estimate.
+        .with_gas_budget(Some(10_000_000));
+    (server, client)
+}
+
+/// Every JSON-RPC call the server received, in order, as `(method, params)`.
+async fn calls(server: &MockServer) -> Vec<(String, Value)> {
+    server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|request| {
+            let body: Value = serde_json::from_slice(&request.body).unwrap();
+            (body["method"].as_str().unwrap().to_string(), body["params"].clone())
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn full_flow_mints_then_gets_flag() {
+    let (server, client) = start(3).await;
+    // The counter is only read again once the claim is final.
+    let client = client.with_finality_timeout(Some(Duration::from_secs(1)));
+    let sender = client.sender_address().to_string();
+
+    let response1 = client.mint(3).await.unwrap();
+    let mint_coins = client.wait_for_mint_coins(3, Duration::from_secs(1)).await.unwrap();
+    let response2 = client.get_flag(&mint_coins[..3], None).await.unwrap();
+
+    assert_eq!(response1.digest.to_string(), DIGEST);
+    let (flag, flag_type) = client.flag_object(&response2).expect("flag object");
+    assert_eq!(flag, object_id(0xf1));
+    assert_eq!(flag_type.name.as_str(), "Flag");
+
+    let calls = calls(&server).await;
+    let executions: Vec<&Value> = calls
+        .iter()
+        .filter(|(method, _)| method == "iota_executeTransactionBlock")
+        .map(|(_, params)| params)
+        .collect();
+    assert_eq!(executions.len(), 2);
+    for params in executions {
+        assert_eq!(params[1].as_array().unwrap().len(), 1, "one signature");
+        assert_eq!(params[3], json!("WaitForLocalExecution"));
+    }
+
+    // Apart from the package, checked once on connect.
+    let package = json!(deployment().package_id.to_string());
+    let (counter_reads, shared_objects): (Vec<&Value>, Vec<&Value>) = calls
+        .iter()
+        .filter(|(method, params)| method == "iota_getObject" && params[0] != package)
+        .map(|(_, params)| params)
+        .partition(|params| params[1]["showBcs"] == json!(true));
+    let shared_objects: Vec<&Value> = shared_objects.iter().map(|params| &params[0]).collect();
+    assert_eq!(shared_objects, [&json!(object_id(0xa2).to_string()), &json!(object_id(0xa3).to_string())]);
+    // Once before and once after transaction 2 was checkpointed, to check get_flag increased it.
+    assert_eq!(counter_reads.len(), 2);
+    assert!(counter_reads.iter().all(|params| params[0] == json!(object_id(0xa3).to_string())));
+
+    assert!(
+        calls
+            .iter()
+            .filter(|(method, _)| method == "iotax_getCoins")
+            .all(|(_, params)| params[0] == json!(sender))
+    );
+}
+
+#[tokio::test]
+async fn runner_reuses_existing_coins_and_returns_the_outcome() {
+    let (server, client) = start(3).await;
+    let sender = client.sender_address();
+
+    let outcome = ChallengeRunner::new(client).run().await.unwrap();
+
+    assert_eq!(outcome.sender, sender);
+    assert_eq!(outcome.tx1_digest, None);
+    assert_eq!(outcome.tx2_digest.to_string(), DIGEST);
+    assert_eq!(outcome.flag_object, Some(object_id(0xf1)));
+    // 1000 + 2000 - 500
+    assert_eq!(outcome.gas_used(), 2500);
+    assert_eq!(outcome.tx1_gas_cost, None);
+    assert_eq!(outcome.gas_cost(), outcome.tx2_gas_cost);
+    assert_eq!(outcome.gas_cost().storage_rebate, 500);
+    // Transaction 1 was skipped, so it was never timed.
+    let steps: Vec<&str> = outcome.timings.iter().map(|(step, _)| step.id()).collect();
+    assert_eq!(steps, ["prepare", "sync", "tx2"]);
+    let executions = calls(&server)
+        .await
+        .into_iter()
+        .filter(|(method, _)| method == "iota_executeTransactionBlock")
+        .count();
+    assert_eq!(executions, 1);
+}
+
+#[tokio::test]
+async fn runner_timeout_names_the_step_it_stopped_in() {
+    // The minted coins are never indexed, so the run hangs waiting for them.
+    let (_server, client) = start(0).await;
+
+    let error = ChallengeRunner::new(client)
+        .with_coin_timeout(Duration::from_secs(30))
+        .with_timeout(Some(Duration::from_secs(1)))
+        .run()
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, ChallengeError::Timeout { step: Step::WaitForCoins, .. }));
+    assert_eq!(error.to_string(), "timed out after 1s during the wait for MINTCOINs to be indexed");
+}
+
+#[tokio::test]
+async fn mint_coins_returns_the_created_coins_without_polling() {
+    let (server, client) = start(0).await;
+
+    let coins = client.mint_coins(3).await.unwrap();
+
+    let ids: Vec<_> = coins.iter().map(|coin| coin.0).collect();
+    assert_eq!(ids, [object_id(0x20), object_id(0x21), object_id(0x22)]);
+    // Only the gas coin lookup; the minted coins are never queried.
+    let coin_queries = calls(&server)
+        .await
+        .into_iter()
+        .filter(|(method, _)| method == "iotax_getCoins")
+        .count();
+    assert_eq!(coin_queries, 1);
+}
+
+#[tokio::test]
+async fn fewer_than_three_coins_is_an_error() {
+    let (server, client) = start(2).await;
+
+    let error = client
+        .wait_for_mint_coins(3, Duration::from_millis(600))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, ChallengeError::InsufficientMintCoins { needed: 3, found: 2, .. }));
+
+    let calls = calls(&server).await;
+    assert!(calls.iter().all(|(method, _)| method != "iota_executeTransactionBlock"));
+}
+
+#[tokio::test]
+async fn rpc_headers_are_sent_with_every_request() {
+    let signer = Signer::InMemory(InMemKeystore::new_insecure_for_tests(1));
+    let sender = signer.addresses()[0];
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(RpcResponder(node(sender, 3)))
+        .mount(&server)
+        .await;
+    let api_key: RpcHeader = "X-Api-Key: secret".parse().unwrap();
+
+    let config = ChallengeConfig {
+        rpc_headers: vec![api_key],
+        ..config(&server)
+    };
+
+    ChallengeClient::connect(&config, signer, SenderSelection::First, Arc::new(HumanReporter))
+        .await
+        .unwrap();
+
+    let requests = server.received_requests().await.unwrap();
+    assert!(!requests.is_empty());
+    assert!(requests.iter().all(|request| request.headers.get("x-api-key").is_some_and(|value| value == "secret")));
+}
+
+#[tokio::test]
+async fn unreachable_endpoint_fails_over_to_the_next() {
+    let signer = Signer::InMemory(InMemKeystore::new_insecure_for_tests(1));
+    let sender = signer.addresses()[0];
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(RpcResponder(node(sender, 3)))
+        .mount(&server)
+        .await;
+    let config = ChallengeConfig {
+        // Nothing listens on port 1, so connecting is refused.
+        rpc_url: "http://127.0.0.1:1".to_string(),
+        fallback_rpc_urls: vec![server.uri()],
+        ..config(&server)
+    };
+
+    let client = ChallengeClient::connect(&config, signer, SenderSelection::First, Arc::new(HumanReporter))
+        .await
+        .unwrap();
+    let coins = client.owned_mint_coins().await.unwrap();
+
+    assert_eq!(coins.len(), 3);
+    assert!(!server.received_requests().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn returned_flag_goes_back_to_the_sender_without_a_recipient() {
+    let signer = Signer::InMemory(InMemKeystore::new_insecure_for_tests(1));
+    let sender = signer.addresses()[0];
+    let package = deployment().package_id;
+    let node = node(sender, 3);
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(RpcResponder(move |method: &str, params: &Value| match method {
+            "iota_getNormalizedMoveModulesByPackage" => {
+                let mut module = mintcoin_module(package);
+                let flag = json!({
+                    "address": package.to_string(),
+                    "module": "mintcoin",
+                    "name": "Flag",
+                    "typeArguments": [],
+                });
+                module["exposedFunctions"]["get_flag"]["return"] = json!([{ "Struct": flag }]);
+                json!({ "mintcoin": module })
+            }
+            _ => node(method, params),
+        }))
+        .mount(&server)
+        .await;
+    let client = ChallengeClient::connect(&config(&server), signer, SenderSelection::First, Arc::new(HumanReporter))
+        .await
+        .unwrap()
+        .with_gas_budget(Some(10_000_000));
+
+    let mint_coins = client.owned_mint_coins().await.unwrap();
+    let tx_data = client.build_get_flag_tx(&mint_coins, None).await.unwrap();
+
+    let TransactionKind::ProgrammableTransaction(pt) = tx_data.kind() else {
+        panic!("transaction 2 is not a PTB");
+    };
+    let Some(Command::TransferObjects(flags, recipient)) = pt.commands.last() else {
+        panic!("the flag is not transferred last");
+    };
+    // join, join, split, then get_flag
+    assert_eq!(flags, &[Argument::Result(3)]);
+    let Argument::Input(recipient) = recipient else {
+        panic!("the recipient is not an input");
+    };
+    assert_eq!(pt.inputs[*recipient as usize], CallArg::Pure(bcs::to_bytes(&sender).unwrap()));
+}
+
+#[tokio::test]
+async fn package_without_mintcoin_is_rejected_on_connect() {
+    let signer = Signer::InMemory(InMemKeystore::new_insecure_for_tests(1));
+    let node = node(signer.addresses()[0], 3);
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(RpcResponder(move |method: &str, params: &Value| match method {
+            "iota_getNormalizedMoveModulesByPackage" => json!({}),
+            _ => node(method, params),
+        }))
+        .mount(&server)
+        .await;
+
+    let error = ChallengeClient::connect(&config(&server), signer, SenderSelection::First, Arc::new(HumanReporter))
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(error, ChallengeError::MissingModule { module: "mintcoin", .. }));
+}
+
+#[tokio::test]
+async fn unresponsive_node_times_out() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(30)))
+        .mount(&server)
+        .await;
+    let config = ChallengeConfig {
+        request_timeout: Duration::from_secs(1),
+        ..config(&server)
+    };
+    let signer = Signer::InMemory(InMemKeystore::new_insecure_for_tests(1));
+
+    let error = ChallengeClient::connect(&config, signer, SenderSelection::First, Arc::new(HumanReporter))
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(error, ChallengeError::ConnectTimeout { .. }));
+}
+
+#[tokio::test]
+async fn unchanged_or_unreadable_counter_does_not_fail_the_claim() {
+    for counter in [counter_bcs(0), vec![1, 2, 3]] {
+        let signer = Signer::InMemory(InMemKeystore::new_insecure_for_tests(1));
+        let node = node(signer.addresses()[0], 3);
+        let package = deployment().package_id;
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(RpcResponder(move |method: &str, params: &Value| match method {
+                // A node still serving the counter from before the claim, or a layout that doesn't decode.
+                "iota_getObject" if params[1]["showBcs"] == json!(true) => json!({
+                    "data": {
+                        "objectId": params[0],
+                        "version": "1",
+                        "digest": DIGEST,
+                        "bcs": IotaRawData::MoveObject(IotaRawMoveObject {
+                            type_: format!("{}::mintcoin::Counter", package).parse().unwrap(),
+                            has_public_transfer: false,
+                            version: SequenceNumber::from_u64(1),
+                            bcs_bytes: counter.clone(),
+                        }),
+                    }
+                }),
+                _ => node(method, params),
+            }))
+            .mount(&server)
+            .await;
+        let client =
+            ChallengeClient::connect(&config(&server), signer, SenderSelection::First, Arc::new(HumanReporter))
+                .await
+                .unwrap()
+                .with_gas_budget(Some(10_000_000))
+                .with_finality_timeout(Some(Duration::from_secs(1)));
+
+        let mint_coins = client.owned_mint_coins().await.unwrap();
+        let response = client.get_flag(&mint_coins, None).await.unwrap();
+        assert_eq!(response.digest.to_string(), DIGEST);
+    }
+}
+
+#[test]
+fn counter_value_is_decoded_from_bcs() {
+    assert_eq!(decode_counter(&counter_bcs(7)).unwrap(), 7);
+    // Fields a deployment adds after the value are ignored; a truncated counter is rejected rather than misread.
+    let mut extended = counter_bcs(7);
+    extended.extend_from_slice(&[1, 2, 3]);
+    assert_eq!(decode_counter(&extended).unwrap(), 7);
+    assert!(decode_counter(&counter_bcs(7)[..36]).is_err());
+}
@@ -0,0 +1,130 @@
+//! Gas coin selection never picks an object the PTB already uses as an input, `--explain` plans the same coins a run
+//! uses, budgets cover computation, costs add up across transactions and the gas price cap is enforced.
+
+mod common;
+
+use common::coin;
+use iota_challenge3_example::{
+    ChallengeError, GAS_SAFE_OVERHEAD, SplitPlan, check_gas_price, faucet::GAS_COIN_TYPE, gas_budget,
+    input_object_ids, net_gas_used, pick_gas_coin, select_coins, total_gas_cost,
+};
+use iota_sdk::types::{
+    base_types::{ObjectID, SequenceNumber},
+    gas::GasCostSummary,
+    programmable_transaction_builder::ProgrammableTransactionBuilder,
+    transaction::{CallArg, ObjectArg},
+};
+
+#[test]
+fn ptb_inputs_are_never_picked_for_gas() {
+    // The largest coin is also the first MINTCOIN input, as on a network where MINTCOIN pays for gas.
+    let coins = [coin(GAS_COIN_TYPE, 1, 900), coin(GAS_COIN_TYPE, 2, 500), coin(GAS_COIN_TYPE, 3, 100)];
+
+    let mut ptb = ProgrammableTransactionBuilder::new();
+    ptb.input(CallArg::Object(ObjectArg::ImmOrOwnedObject(coins[0].object_ref())))
+        .unwrap();
+    ptb.input(CallArg::Object(ObjectArg::SharedObject {
+        id: ObjectID::from_single_byte(9),
+        initial_shared_version: SequenceNumber::from_u64(1),
+        mutable: true,
+    }))
+    .unwrap();
+    ptb.input(CallArg::Pure(bcs::to_bytes(&5u64).unwrap())).unwrap();
+    let inputs = input_object_ids(&ptb.finish());
+
+    assert_eq!(inputs, [ObjectID::from_single_byte(1), ObjectID::from_single_byte(9)]);
+
+    let gas = pick_gas_coin(&coins, 200, &inputs).unwrap();
+    assert_eq!(gas.coin_object_id, ObjectID::from_single_byte(2));
+}
+
+#[test]
+fn excluding_every_coin_means_no_gas_coin() {
+    let coins = [coin(GAS_COIN_TYPE, 1, 900)];
+    let error = pick_gas_coin(&coins, 200, &[ObjectID::from_single_byte(1)]).unwrap_err();
+    assert!(matches!(error, ChallengeError::NoGasCoin));
+}
+
+#[test]
+fn selection_merges_the_first_mint_coins_and_pays_with_another_coin() {
+    // MINTCOIN is the gas coin type here, so the largest coin is also one of the MINTCOINs to merge.
+    let coins = vec![coin(GAS_COIN_TYPE, 1, 900), coin(GAS_COIN_TYPE, 2, 4), coin(GAS_COIN_TYPE, 3, 500)];
+    let selection = select_coins(coins.clone(), coins, 2, 200, 5, 1).unwrap();
+
+    let merged: Vec<_> = selection.mint_coins.iter().map(|coin| coin.coin_object_id).collect();
+    assert_eq!(merged, [ObjectID::from_single_byte(1), ObjectID::from_single_byte(2)]);
+    assert_eq!(selection.gas_coin.coin_object_id, ObjectID::from_single_byte(3));
+    let split = selection.split.unwrap();
+    assert_eq!(split, SplitPlan { merged_balance: 904, flag_coin_value: 5, flag_count: 1 });
+    assert_eq!(split.remainder(), 899);
+}
+
+#[test]
+fn selection_without_enough_mint_coins_plans_no_split() {
+    let gas_coins = vec![coin(GAS_COIN_TYPE, 1, 900)];
+    let selection = select_coins(gas_coins, vec![coin("0x2::mintcoin::MINTCOIN", 2, 3)], 3, 200, 5, 1).unwrap();
+
+    assert!(selection.mint_coins.is_empty());
+    assert_eq!(selection.split, None);
+    assert_eq!(selection.gas_coin.coin_object_id, ObjectID::from_single_byte(1));
+}
+
+#[test]
+fn selection_fails_like_the_run_when_mint_coins_hold_too_little() {
+    let gas_coins = vec![coin(GAS_COIN_TYPE, 1, 900)];
+    let mint_coins = vec![coin("0x2::mintcoin::MINTCOIN", 2, 1), coin("0x2::mintcoin::MINTCOIN", 3, 1)];
+    let error = select_coins(gas_coins, mint_coins, 2, 200, 5, 1).unwrap_err();
+    assert!(matches!(error, ChallengeError::InsufficientMintBalance { needed: 5, available: 2 }));
+}
+
+#[test]
+fn gas_budget_scales_net_usage() {
+    let gas_cost = GasCostSummary {
+        computation_cost: 1_000_000,
+        computation_cost_burned: 1_000_000,
+        storage_cost: 2_000_000,
+        storage_rebate: 500_000,
+        non_refundable_storage_fee: 0,
+    };
+    assert_eq!(gas_budget(&gas_cost, 1000, 1.2), 3_000_000);
+}
+
+#[test]
+fn gas_budget_never_drops_below_computation_when_the_rebate_covers_storage() {
+    // Freeing coin objects can rebate more than the new objects cost to store.
+    let gas_cost = GasCostSummary {
+        computation_cost: 1_000_000,
+        computation_cost_burned: 1_000_000,
+        storage_cost: 2_000_000,
+        storage_rebate: 5_000_000,
+        non_refundable_storage_fee: 0,
+    };
+    assert_eq!(gas_budget(&gas_cost, 1000, 1.2), 1_000_000 + GAS_SAFE_OVERHEAD * 1000);
+}
+
+#[test]
+fn gas_costs_of_both_transactions_add_up() {
+    let cost = |computation_cost, storage_cost, storage_rebate| GasCostSummary {
+        computation_cost,
+        computation_cost_burned: computation_cost,
+        storage_cost,
+        storage_rebate,
+        non_refundable_storage_fee: 0,
+    };
+    let tx1 = cost(1_000_000, 3_000_000, 1_000_000);
+    let tx2 = cost(1_000_000, 2_000_000, 2_500_000);
+
+    let total = total_gas_cost([&tx1, &tx2]);
+    assert_eq!(total, cost(2_000_000, 5_000_000, 3_500_000));
+    assert_eq!(net_gas_used(&total), net_gas_used(&tx1) + net_gas_used(&tx2));
+}
+
+#[test]
+fn gas_price_above_the_cap_is_refused() {
+    assert!(check_gas_price(1000, None).is_ok());
+    assert!(check_gas_price(1000, Some(1000)).is_ok());
+    assert!(matches!(
+        check_gas_price(1500, Some(1000)),
+        Err(ChallengeError::GasPriceTooHigh { reference: 1500, cap: 1000 })
+    ));
+}
@@ -0,0 +1,474 @@
+//! Coin selection, PTB construction and execution against an in-memory `CoinSource` and `TxExecutor`.
+
+mod common;
+
+use common::{DIGEST, coin, deployment, effects, object_id};
+use iota_challenge3_example::{
+    ChallengeError, CoinSource, Deployment, ExecuteConfig, GAS_BUDGET, GasConfig, HumanReporter, MoveAbort, Signer,
+    Stage, TxExecutor, WaitStrategy, attach_gas, faucet::GAS_COIN_TYPE, get_flag_ptb, mint_ptb, sign_and_execute,
+    single_tx_ptb, wait_for_coins,
+};
+use iota_keys::keystore::InMemKeystore;
+use iota_sdk::{
+    error::Error,
+    rpc_types::{Coin, IotaTransactionBlockEffects, IotaTransactionBlockResponse, IotaTransactionBlockResponseOptions},
+    types::{
+        base_types::{IotaAddress, ObjectID, ObjectRef, SequenceNumber},
+        transaction::{
+            Argument, CallArg, Command, ObjectArg, ProgrammableTransaction, Transaction, TransactionData,
+            TransactionDataAPI,
+        },
+    },
+};
+use serde_json::{Value, json};
+use std::sync::Mutex;
+use std::time::Duration;
+
+const MINTCOIN: &str = "0xa1::mintcoin::MINTCOIN";
+
+/// Serves a fixed set of coins and records the coin type of every query. Coins listed in `stale` have moved on
+/// to a later version than the one served.
+#[derive(Default)]
+struct MockCoins {
+    coins: Vec<Coin>,
+    stale: Vec<ObjectID>,
+    queries: Mutex<Vec<Option<String>>>,
+}
+
+impl CoinSource for MockCoins {
+    async fn get_coins(&self, _owner: IotaAddress, coin_type: Option<String>) -> Result<Vec<Coin>, Error> {
+        self.queries.lock().unwrap().push(coin_type.clone());
+        let coin_type = coin_type.unwrap_or_else(|| GAS_COIN_TYPE.to_string());
+        Ok(self.coins.iter().filter(|coin| coin.coin_type == coin_type).cloned().collect())
+    }
+
+    async fn get_reference_gas_price(&self) -> Result<u64, Error> {
+        Ok(1000)
+    }
+
+    async fn latest_object_ref(&self, id: ObjectID) -> Result<Option<ObjectRef>, Error> {
+        let coin = self.coins.iter().find(|coin| coin.coin_object_id == id);
+        Ok(coin.map(|coin| {
+            let (id, version, digest) = coin.object_ref();
+            if self.stale.contains(&id) {
+                (id, SequenceNumber::from_u64(version.value() + 1), digest)
+            } else {
+                (id, version, digest)
+            }
+        }))
+    }
+}
+
+/// Simulates every transaction with the `dry_run` effects and records each submitted transaction.
+struct MockExecutor {
+    dry_run: Value,
+    submitted: Mutex<Vec<Transaction>>,
+}
+
+impl MockExecutor {
+    fn new(dry_run: Value) -> Self {
+        Self {
+            dry_run,
+            submitted: Mutex::default(),
+        }
+    }
+}
+
+impl TxExecutor for MockExecutor {
+    async fn execute_transaction_block(
+        &self,
+        tx: Transaction,
+        _options: IotaTransactionBlockResponseOptions,
+        _wait: WaitStrategy,
+    ) -> Result<IotaTransactionBlockResponse, Error> {
+        let sender = tx.transaction_data().sender();
+        self.submitted.lock().unwrap().push(tx);
+        Ok(serde_json::from_value(json!({
+            "digest": DIGEST,
+            "effects": effects(sender, json!({ "status": "success" }), 1000, 2000, 500),
+            "confirmedLocalExecution": true,
+        }))
+        .unwrap())
+    }
+
+    async fn dry_run_transaction_block(&self, _tx_data: TransactionData) -> Result<IotaTransactionBlockEffects, Error> {
+        Ok(serde_json::from_value(self.dry_run.clone()).unwrap())
+    }
+}
+
+fn shared(id: u8) -> ObjectArg {
+    ObjectArg::SharedObject {
+        id: object_id(id),
+        initial_shared_version: SequenceNumber::from_u64(1),
+        mutable: true,
+    }
+}
+
+fn counter() -> ObjectArg {
+    shared(0xa3)
+}
+
+/// Unsigned transaction 1 minting `count` MINTCOINs, paid for with a single gas coin.
+fn mint_tx(sender: IotaAddress, count: u32) -> TransactionData {
+    let pt = mint_ptb(&deployment(), shared(0xa2), count, &HumanReporter).unwrap();
+    let gas_coin = coin(GAS_COIN_TYPE, 1, 1_000_000_000).object_ref();
+    TransactionData::new_programmable(sender, vec![gas_coin], pt, GAS_BUDGET, 1000)
+}
+
+/// `module::function` of every Move call in `pt`, and `transfer` for every object transfer.
+fn commands(pt: &ProgrammableTransaction) -> Vec<String> {
+    pt.commands
+        .iter()
+        .map(|command| match command {
+            Command::MoveCall(call) => format!("{}::{}", call.module, call.function),
+            Command::TransferObjects(..) => "transfer".to_string(),
+            other => panic!("unexpected command {}", other),
+        })
+        .collect()
+}
+
+fn signer() -> (Signer, IotaAddress) {
+    let signer = Signer::InMemory(InMemKeystore::new_insecure_for_tests(1));
+    let sender = signer.addresses()[0];
+    (signer, sender)
+}
+
+#[tokio::test]
+async fn given_gas_coin_that_is_an_input_is_replaced() {
+    // As under `--gas-coin-type <MINTCOIN>`: transaction 1 paid with MINTCOIN 2, which transaction 2 also merges.
+    let mint_coins = vec![coin(MINTCOIN, 1, 3), coin(MINTCOIN, 2, 3)];
+    let mut coins = mint_coins.clone();
+    coins.push(coin(MINTCOIN, 3, 500));
+    let source = MockCoins {
+        coins,
+        ..MockCoins::default()
+    };
+    let sender = IotaAddress::random_for_testing_only();
+    let gas = GasConfig {
+        budget: Some(100),
+        ..GasConfig::estimated(1000)
+    };
+
+    let pt = get_flag_ptb(&deployment(), counter(), &mint_coins, 5, 1, sender, None, &HumanReporter).unwrap();
+    let tx_data = attach_gas(&source, sender, pt, Some(mint_coins[1].object_ref()), Some(MINTCOIN.to_string()), &gas)
+        .await
+        .unwrap();
+
+    assert_eq!(tx_data.gas()[0].0, object_id(3));
+    assert_eq!(*source.queries.lock().unwrap(), [Some(MINTCOIN.to_string())]);
+}
+
+#[tokio::test]
+async fn stale_gas_coin_is_skipped() {
+    // Coin 3 is the largest, but the index still serves the version from before transaction 1 paid with it.
+    let mint_coins = vec![coin(MINTCOIN, 1, 3), coin(MINTCOIN, 2, 3)];
+    let source = MockCoins {
+        coins: vec![coin(GAS_COIN_TYPE, 3, 500), coin(GAS_COIN_TYPE, 4, 400)],
+        stale: vec![object_id(3)],
+        ..MockCoins::default()
+    };
+    let sender = IotaAddress::random_for_testing_only();
+    let gas = GasConfig {
+        budget: Some(100),
+        ..GasConfig::estimated(1000)
+    };
+
+    let pt = get_flag_ptb(&deployment(), counter(), &mint_coins, 5, 1, sender, None, &HumanReporter).unwrap();
+    let tx_data = attach_gas(&source, sender, pt, None, None, &gas).await.unwrap();
+
+    assert_eq!(tx_data.gas()[0].0, object_id(4));
+}
+
+#[tokio::test]
+async fn sponsor_gas_coin_pays_even_when_not_the_largest() {
+    let mint_coins = vec![coin(MINTCOIN, 1, 3), coin(MINTCOIN, 2, 3)];
+    let source = MockCoins {
+        coins: vec![coin(GAS_COIN_TYPE, 3, 500), coin(GAS_COIN_TYPE, 4, 400)],
+        ..MockCoins::default()
+    };
+    let (sender, sponsor) = (IotaAddress::random_for_testing_only(), IotaAddress::random_for_testing_only());
+    let gas = GasConfig {
+        budget: Some(100),
+        sponsor: Some(sponsor),
+        sponsor_gas_coin: Some(object_id(4)),
+        ..GasConfig::estimated(1000)
+    };
+
+    let pt = get_flag_ptb(&deployment(), counter(), &mint_coins, 5, 1, sender, None, &HumanReporter).unwrap();
+    let tx_data = attach_gas(&source, sender, pt, None, None, &gas).await.unwrap();
+
+    assert_eq!(tx_data.gas()[0].0, object_id(4));
+    assert_eq!(tx_data.gas_owner(), sponsor);
+}
+
+#[tokio::test]
+async fn only_stale_gas_coins_is_an_error() {
+    let mint_coins = vec![coin(MINTCOIN, 1, 3), coin(MINTCOIN, 2, 3)];
+    let source = MockCoins {
+        coins: vec![coin(GAS_COIN_TYPE, 3, 500)],
+        stale: vec![object_id(3)],
+        ..MockCoins::default()
+    };
+    let sender = IotaAddress::random_for_testing_only();
+    let gas = GasConfig {
+        budget: Some(100),
+        ..GasConfig::estimated(1000)
+    };
+
+    let pt = get_flag_ptb(&deployment(), counter(), &mint_coins, 5, 1, sender, None, &HumanReporter).unwrap();
+    let error = attach_gas(&source, sender, pt, None, None, &gas).await.unwrap_err();
+
+    assert!(matches!(error, ChallengeError::StaleGasCoins(ids) if ids == [object_id(3)]));
+}
+
+#[tokio::test]
+async fn given_gas_coin_is_used_without_a_lookup() {
+    let mint_coins = vec![coin(MINTCOIN, 1, 3), coin(MINTCOIN, 2, 3)];
+    let source = MockCoins::default();
+    let sender = IotaAddress::random_for_testing_only();
+    let gas_coin = coin(GAS_COIN_TYPE, 9, 1_000_000_000).object_ref();
+
+    let pt = get_flag_ptb(&deployment(), counter(), &mint_coins, 5, 1, sender, None, &HumanReporter).unwrap();
+    let tx_data = attach_gas(&source, sender, pt, Some(gas_coin), None, &GasConfig::estimated(1000))
+        .await
+        .unwrap();
+
+    assert_eq!(tx_data.gas(), [gas_coin]);
+    assert!(source.queries.lock().unwrap().is_empty());
+}
+
+#[test]
+fn get_flag_ptb_merges_splits_and_returns_both_coins() {
+    let mint_coins = [coin(MINTCOIN, 1, 2), coin(MINTCOIN, 2, 2), coin(MINTCOIN, 3, 2)];
+    let sender = IotaAddress::random_for_testing_only();
+
+    let pt = get_flag_ptb(&deployment(), counter(), &mint_coins, 5, 1, sender, None, &HumanReporter).unwrap();
+
+    // counter, three coins, the split value and the recipient
+    assert_eq!(pt.inputs.len(), 6);
+    assert_eq!(pt.inputs[0], CallArg::Object(counter()));
+    assert_eq!(
+        commands(&pt),
+        ["coin::join", "coin::join", "coin::split", "mintcoin::get_flag", "transfer", "transfer"]
+    );
+}
+
+#[test]
+fn exact_balance_is_passed_to_get_flag_without_a_split() {
+    let mint_coins = [coin(MINTCOIN, 1, 2), coin(MINTCOIN, 2, 3)];
+    let sender = IotaAddress::random_for_testing_only();
+
+    let pt = get_flag_ptb(&deployment(), counter(), &mint_coins, 5, 1, sender, None, &HumanReporter).unwrap();
+
+    // counter, two coins and the recipient; no split value
+    assert_eq!(pt.inputs.len(), 4);
+    assert_eq!(commands(&pt), ["coin::join", "mintcoin::get_flag", "transfer"]);
+    let Command::MoveCall(get_flag) = &pt.commands[1] else {
+        panic!("get_flag is not the second command");
+    };
+    // The merged coin is the first coin input.
+    assert_eq!(get_flag.arguments[1], Argument::Input(1));
+}
+
+#[test]
+fn consumed_flag_coin_is_not_transferred_back() {
+    let consuming = Deployment {
+        get_flag_consumes_coin: Some(true),
+        ..deployment()
+    };
+    let mint_coins = [coin(MINTCOIN, 1, 2), coin(MINTCOIN, 2, 4)];
+    let sender = IotaAddress::random_for_testing_only();
+
+    let pt = get_flag_ptb(&consuming, counter(), &mint_coins, 5, 1, sender, None, &HumanReporter).unwrap();
+
+    assert_eq!(commands(&pt), ["coin::join", "coin::split", "mintcoin::get_flag", "transfer"]);
+    let Some(Command::TransferObjects(objects, _)) = pt.commands.last() else {
+        panic!("the remainder is not transferred last");
+    };
+    // Only the remainder: the merged coin, i.e. the first coin input.
+    assert_eq!(objects, &[Argument::Input(1)]);
+}
+
+#[test]
+fn renamed_coin_type_is_joined_and_split() {
+    let forked = Deployment {
+        coin_module: "forkcoin".to_string(),
+        coin_struct: "FORKCOIN".to_string(),
+        ..deployment()
+    };
+    let mint_coins = [coin(MINTCOIN, 1, 3), coin(MINTCOIN, 2, 3)];
+    let sender = IotaAddress::random_for_testing_only();
+
+    let pt = get_flag_ptb(&forked, counter(), &mint_coins, 5, 1, sender, None, &HumanReporter).unwrap();
+
+    assert_eq!(forked.mint_coin_type(), format!("{}::forkcoin::FORKCOIN", forked.package_id));
+    let type_arguments: Vec<String> = pt
+        .commands
+        .iter()
+        .filter_map(|command| match command {
+            Command::MoveCall(call) if call.module.as_str() == "coin" => Some(call.type_arguments[0].to_string()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(type_arguments.len(), 2);
+    assert!(type_arguments.iter().all(|type_argument| type_argument.ends_with("::forkcoin::FORKCOIN")));
+}
+
+#[test]
+fn single_tx_ptb_chains_the_minted_coins_into_get_flag() {
+    let sender = IotaAddress::random_for_testing_only();
+
+    let pt = single_tx_ptb(&deployment(), shared(0xa2), counter(), 3, 5, 1, sender, None, &HumanReporter).unwrap();
+
+    // treasury cap, counter, the split value and the recipient; no coin objects
+    assert_eq!(pt.inputs.len(), 4);
+    assert_eq!(
+        commands(&pt),
+        [
+            "mintcoin::mint_coin",
+            "mintcoin::mint_coin",
+            "mintcoin::mint_coin",
+            "coin::join",
+            "coin::join",
+            "coin::split",
+            "mintcoin::get_flag",
+            "transfer",
+            "transfer"
+        ]
+    );
+}
+
+#[test]
+fn returned_flag_is_sent_to_the_flag_recipient() {
+    let mint_coins = [coin(MINTCOIN, 1, 2), coin(MINTCOIN, 2, 4)];
+    let sender = IotaAddress::random_for_testing_only();
+    let flag_recipient = IotaAddress::random_for_testing_only();
+
+    let pt = get_flag_ptb(&deployment(), counter(), &mint_coins, 5, 1, sender, Some(flag_recipient), &HumanReporter)
+        .unwrap();
+
+    assert_eq!(commands(&pt), ["coin::join", "coin::split", "mintcoin::get_flag", "transfer", "transfer", "transfer"]);
+    let Some(Command::TransferObjects(objects, Argument::Input(recipient))) = pt.commands.last() else {
+        panic!("the flag is not transferred last");
+    };
+    // The result of get_flag, the third command.
+    assert_eq!(objects, &[Argument::Result(2)]);
+    assert_eq!(pt.inputs[*recipient as usize], CallArg::Pure(bcs::to_bytes(&flag_recipient).unwrap()));
+}
+
+#[test]
+fn get_flag_ptb_requires_the_flag_coin_value() {
+    let mint_coins = [coin(MINTCOIN, 1, 2), coin(MINTCOIN, 2, 2)];
+    let sender = IotaAddress::random_for_testing_only();
+
+    let error = get_flag_ptb(&deployment(), counter(), &mint_coins, 5, 1, sender, None, &HumanReporter).unwrap_err();
+    assert!(matches!(error, ChallengeError::InsufficientMintBalance { needed: 5, available: 4 }));
+}
+
+#[test]
+fn flag_count_splits_and_claims_once_per_coin() {
+    let mint_coins = [coin(MINTCOIN, 1, 6), coin(MINTCOIN, 2, 6)];
+    let sender = IotaAddress::random_for_testing_only();
+
+    let pt = get_flag_ptb(&deployment(), counter(), &mint_coins, 5, 2, sender, None, &HumanReporter).unwrap();
+
+    assert_eq!(
+        commands(&pt),
+        ["coin::join", "coin::split", "coin::split", "mintcoin::get_flag", "mintcoin::get_flag", "transfer", "transfer"]
+    );
+    let Command::TransferObjects(objects, _) = &pt.commands[5] else {
+        panic!("the flag coins are not transferred after the claims");
+    };
+    assert_eq!(objects, &[Argument::Result(1), Argument::Result(2)]);
+
+    let error = get_flag_ptb(&deployment(), counter(), &mint_coins, 5, 3, sender, None, &HumanReporter).unwrap_err();
+    assert!(matches!(error, ChallengeError::InsufficientMintBalance { needed: 15, available: 12 }));
+}
+
+#[tokio::test]
+async fn wait_for_coins_returns_once_enough_are_owned() {
+    let source = MockCoins {
+        coins: vec![coin(MINTCOIN, 1, 1), coin(MINTCOIN, 2, 1), coin(GAS_COIN_TYPE, 3, 1)],
+        ..MockCoins::default()
+    };
+    let owner = IotaAddress::random_for_testing_only();
+
+    let coins = wait_for_coins(&source, owner, MINTCOIN, 2, Duration::ZERO, None, &HumanReporter).await.unwrap();
+    assert_eq!(coins.len(), 2);
+
+    let error = wait_for_coins(&source, owner, MINTCOIN, 3, Duration::ZERO, None, &HumanReporter).await.unwrap_err();
+    assert!(matches!(error, ChallengeError::CoinWaitTimeout { found: 2, attempts: 1, .. }));
+}
+
+#[tokio::test]
+async fn wait_for_coins_gives_up_after_max_attempts() {
+    let source = MockCoins {
+        coins: vec![coin(MINTCOIN, 1, 1)],
+        ..MockCoins::default()
+    };
+    let owner = IotaAddress::random_for_testing_only();
+
+    let error = wait_for_coins(&source, owner, MINTCOIN, 2, Duration::MAX, Some(3), &HumanReporter).await.unwrap_err();
+    assert!(matches!(error, ChallengeError::CoinWaitTimeout { found: 1, attempts: 3, .. }));
+    assert!(error.to_string().contains("lookups: 3"), "{error}");
+    assert_eq!(source.queries.lock().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn wait_for_coins_accepts_an_unrepresentable_timeout() {
+    let source = MockCoins {
+        coins: vec![coin(MINTCOIN, 1, 1)],
+        ..MockCoins::default()
+    };
+    let owner = IotaAddress::random_for_testing_only();
+
+    let coins = wait_for_coins(&source, owner, MINTCOIN, 1, Duration::MAX, None, &HumanReporter).await.unwrap();
+    assert_eq!(coins.len(), 1);
+}
+
+#[tokio::test]
+async fn sign_and_execute_estimates_the_budget_then_signs_and_submits_once() {
+    let (signer, sender) = signer();
+    let executor = MockExecutor::new(effects(sender, json!({ "status": "success" }), 1_000_000, 2_000_000, 500_000));
+
+    let response = sign_and_execute(
+        &executor,
+        &signer,
+        mint_tx(sender, 3),
+        Stage::Mint,
+        &GasConfig::estimated(1000),
+        &ExecuteConfig::default(),
+        &HumanReporter,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.digest.to_string(), DIGEST);
+    let submitted = executor.submitted.lock().unwrap();
+    assert_eq!(submitted.len(), 1);
+    // (1_000_000 + 2_000_000 - 500_000) * 1.2
+    assert_eq!(submitted[0].transaction_data().gas_budget(), 3_000_000);
+}
+
+#[tokio::test]
+async fn aborting_dry_run_is_never_submitted() {
+    let (signer, sender) = signer();
+    let abort = "MoveAbort(MoveLocation { module: ModuleId { address: a1, name: Identifier(\"mintcoin\") }, \
+        function: 0, instruction: 4, function_name: Some(\"mint_coin\") }, 3) in command 0";
+    let executor = MockExecutor::new(effects(sender, json!({ "status": "failure", "error": abort }), 1000, 0, 0));
+
+    let error = sign_and_execute(
+        &executor,
+        &signer,
+        mint_tx(sender, 3),
+        Stage::Mint,
+        &GasConfig::estimated(1000),
+        &ExecuteConfig::default(),
+        &HumanReporter,
+    )
+    .await
+    .unwrap_err();
+
+    assert!(matches!(error, ChallengeError::ExecutionFailed { abort: Some(MoveAbort { code: 3, .. }), .. }));
+    assert!(executor.submitted.lock().unwrap().is_empty());
+}
@@ -0,0 +1,169 @@
+//! Errors are reported together with their underlying causes and exit codes, object changes as a table, effects as a one-line
+//! summary, timings in ms or s and interrupted runs with the transactions they completed.
+
+mod common;
+
+use common::{DIGEST, effects, object_id};
+use iota_challenge3_example::{
+    ChallengeError, HumanReporter, MoveAbort, ObjectChangeSummary, Reporter, Stage,
+    report::{TrackingReporter, error_chain, format_duration, object_change_table},
+    summarize_effects, summarize_object_changes,
+};
+use iota_sdk::{
+    rpc_types::{IotaTransactionBlockEffects, IotaTransactionBlockResponse},
+    types::{base_types::IotaAddress, digests::TransactionDigest},
+};
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn error_chain_lists_every_cause() {
+    let cause = anyhow::anyhow!("permission denied").context("reading ~/.iota/iota_config/iota.keystore");
+    let error = ChallengeError::Keystore(cause);
+
+    assert_eq!(
+        error_chain(&error),
+        [
+            "failed to load keystore",
+            "reading ~/.iota/iota_config/iota.keystore",
+            "permission denied"
+        ]
+    );
+}
+
+#[test]
+fn error_without_a_source_is_its_own_chain() {
+    assert_eq!(error_chain(&ChallengeError::NoGasCoin).len(), 1);
+}
+
+#[test]
+fn exit_codes_tell_error_categories_apart() {
+    let abort = MoveAbort {
+        module: Some("mintcoin".to_string()),
+        function: Some("get_flag".to_string()),
+        code: 3,
+    };
+    let digest: TransactionDigest = DIGEST.parse().unwrap();
+    let failed = |abort| ChallengeError::ExecutionFailed {
+        digest,
+        abort,
+        error: "MoveAbort".to_string(),
+    };
+
+    assert_eq!(ChallengeError::InvalidPrivateKey.exit_code(), 2);
+    assert_eq!(ChallengeError::FinalityTimeout(digest).exit_code(), 3);
+    assert_eq!(failed(Some(abort)).exit_code(), 4);
+    assert_eq!(failed(None).exit_code(), 1);
+    assert_eq!(ChallengeError::NoGasCoin.exit_code(), 1);
+}
+
+#[test]
+fn object_changes_of_transaction_2() {
+    let sender = IotaAddress::random_for_testing_only().to_string();
+    let mint_coin = "0x2::coin::Coin<0xa1::mintcoin::MINTCOIN>";
+    let response: IotaTransactionBlockResponse = serde_json::from_value(json!({
+        "digest": DIGEST,
+        "objectChanges": [
+            {
+                "type": "mutated",
+                "sender": sender,
+                "owner": { "AddressOwner": sender },
+                "objectType": mint_coin,
+                "objectId": object_id(0x10).to_string(),
+                "version": "3",
+                "previousVersion": "2",
+                "digest": DIGEST,
+            },
+            {
+                "type": "deleted",
+                "sender": sender,
+                "objectType": mint_coin,
+                "objectId": object_id(0x11).to_string(),
+                "version": "3",
+            },
+            {
+                "type": "created",
+                "sender": sender,
+                "owner": { "AddressOwner": sender },
+                "objectType": "0xa1::mintcoin::Flag",
+                "objectId": object_id(0xf1).to_string(),
+                "version": "3",
+                "digest": DIGEST,
+            },
+        ],
+    }))
+    .unwrap();
+
+    let changes = summarize_object_changes(&response);
+    let kinds: Vec<(&str, _)> = changes.iter().map(|change| (change.kind, change.object_id)).collect();
+    assert_eq!(
+        kinds,
+        [("mutated", object_id(0x10)), ("deleted", object_id(0x11)), ("created", object_id(0xf1))]
+    );
+    assert!(changes[2].object_type.ends_with("::mintcoin::Flag"));
+}
+
+#[test]
+fn object_change_table_has_a_header_and_one_row_per_object() {
+    let changes = [ObjectChangeSummary {
+        kind: "created",
+        object_id: object_id(0xf1),
+        object_type: "0xa1::mintcoin::Flag".to_string(),
+    }];
+
+    let table = object_change_table(&changes);
+    let lines: Vec<&str> = table.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("kind"));
+    assert!(lines[1].starts_with("created"));
+    assert!(lines[1].contains(&object_id(0xf1).to_string()));
+}
+
+#[test]
+fn effects_of_transaction_1_fit_on_one_line() {
+    let sender = IotaAddress::random_for_testing_only();
+    let mut value = effects(sender, json!({ "status": "success" }), 1_000_000, 1_500_000, 400_000);
+    let owned = |n: u8| {
+        json!({
+            "owner": { "AddressOwner": sender.to_string() },
+            "reference": { "objectId": object_id(n).to_string(), "version": "2", "digest": DIGEST },
+        })
+    };
+    value["created"] = json!([owned(0xc1), owned(0xc2), owned(0xc3)]);
+    value["mutated"] = json!([owned(1)]);
+    let effects: IotaTransactionBlockEffects = serde_json::from_value(value).unwrap();
+
+    let summary = summarize_effects(&effects);
+    assert_eq!((summary.created, summary.mutated, summary.deleted), (3, 1, 0));
+    assert_eq!(summary.gas_used, 2_100_000);
+    assert_eq!(summary.to_string(), "created 3, mutated 1, deleted 0, gas 2.1 mIOTA, success");
+}
+
+#[test]
+fn failed_effects_summary_carries_the_error() {
+    let sender = IotaAddress::random_for_testing_only();
+    let value = effects(sender, json!({ "status": "failure", "error": "InsufficientGas" }), 1000, 0, 0);
+    let effects: IotaTransactionBlockEffects = serde_json::from_value(value).unwrap();
+
+    assert!(summarize_effects(&effects).to_string().ends_with("failure: InsufficientGas"));
+}
+
+#[test]
+fn interrupted_run_lists_the_executed_transactions() {
+    let digest: TransactionDigest = DIGEST.parse().unwrap();
+    let tracker = TrackingReporter::new(Arc::new(HumanReporter));
+    let response: IotaTransactionBlockResponse = serde_json::from_value(json!({ "digest": DIGEST })).unwrap();
+    tracker.executed(Stage::Mint, &response);
+
+    assert_eq!(tracker.completed(), [(Stage::Mint, digest)]);
+    let error = ChallengeError::Interrupted { completed: tracker.completed() };
+    assert_eq!(error.to_string(), format!("interrupted; already executed: Transaction 1 {digest}"));
+}
+
+#[test]
+fn timings_below_a_second_are_in_milliseconds() {
+    assert_eq!(format_duration(Duration::from_millis(320)), "320ms");
+    assert_eq!(format_duration(Duration::from_millis(1200)), "1.2s");
+    assert_eq!(format_duration(Duration::from_millis(4830)), "4.8s");
+}
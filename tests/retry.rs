@@ -0,0 +1,22 @@
+//! Retries back off exponentially, but never for longer than the cap, however many attempts are allowed.
+
+use iota_challenge3_example::retry::{INITIAL_BACKOFF, MAX_BACKOFF, next_backoff};
+use std::time::Duration;
+
+#[test]
+fn backoff_doubles_from_the_initial_delay() {
+    assert_eq!(next_backoff(INITIAL_BACKOFF), Duration::from_millis(200));
+    assert_eq!(next_backoff(Duration::from_millis(200)), Duration::from_millis(400));
+}
+
+#[test]
+fn backoff_is_capped_for_a_high_attempt_count() {
+    // Uncapped, the delay would overflow `Duration` after about 64 doublings.
+    let mut backoff = INITIAL_BACKOFF;
+    for _ in 0..10_000 {
+        backoff = next_backoff(backoff);
+        assert!(backoff <= MAX_BACKOFF);
+    }
+    assert_eq!(backoff, MAX_BACKOFF);
+    assert_eq!(next_backoff(Duration::MAX), MAX_BACKOFF);
+}
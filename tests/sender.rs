@@ -0,0 +1,34 @@
+//! The signing account is picked out of the keystore addresses as selected.
+
+use iota_challenge3_example::{ChallengeError, SenderSelection};
+use iota_sdk::types::base_types::IotaAddress;
+
+fn addresses() -> Vec<IotaAddress> {
+    (0..3).map(|_| IotaAddress::random_for_testing_only()).collect()
+}
+
+#[test]
+fn selections_resolve_to_keystore_addresses() {
+    let addresses = addresses();
+    assert_eq!(SenderSelection::First.resolve(addresses.clone()).unwrap(), addresses[0]);
+    assert_eq!(SenderSelection::Index(2).resolve(addresses.clone()).unwrap(), addresses[2]);
+    assert_eq!(
+        SenderSelection::Address(addresses[1]).resolve(addresses.clone()).unwrap(),
+        addresses[1]
+    );
+}
+
+#[test]
+fn selections_outside_the_keystore_are_errors() {
+    let addresses = addresses();
+    assert!(matches!(SenderSelection::First.resolve(Vec::new()), Err(ChallengeError::NoAddresses)));
+    assert!(matches!(
+        SenderSelection::Index(3).resolve(addresses.clone()),
+        Err(ChallengeError::AddressIndexOutOfRange { index: 3, .. })
+    ));
+    let stranger = IotaAddress::random_for_testing_only();
+    assert!(matches!(
+        SenderSelection::Address(stranger).resolve(addresses),
+        Err(ChallengeError::UnknownSender { requested, .. }) if requested == stranger
+    ));
+}
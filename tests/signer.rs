@@ -0,0 +1,142 @@
+//! Transactions are signed correctly whatever the scheme of the sender's key, also when signed offline.
+
+mod common;
+
+use common::{coin, deployment};
+use iota_challenge3_example::{
+    ChallengeError, GAS_BUDGET, GasConfig, HumanReporter, Signer, faucet::GAS_COIN_TYPE, mint_ptb, offline,
+    signer::SUPPORTED_SCHEMES,
+};
+use iota_keys::keystore::{AccountKeystore, InMemKeystore};
+use iota_sdk::types::{
+    base_types::{IotaAddress, SequenceNumber},
+    crypto::{IotaSignature, Signature, SignatureScheme},
+    transaction::{ObjectArg, TransactionData},
+};
+use shared_crypto::intent::{Intent, IntentMessage};
+
+fn shared_treasury_cap() -> ObjectArg {
+    ObjectArg::SharedObject {
+        id: deployment().treasury_cap_id,
+        initial_shared_version: SequenceNumber::from_u64(1),
+        mutable: true,
+    }
+}
+
+/// Unsigned transaction 1 sent by `sender`.
+fn tx_data1(sender: IotaAddress) -> TransactionData {
+    let pt = mint_ptb(&deployment(), shared_treasury_cap(), 3, &HumanReporter).unwrap();
+    let gas = coin(GAS_COIN_TYPE, 1, 1_000_000_000).object_ref();
+    TransactionData::new_programmable(sender, vec![gas], pt, GAS_BUDGET, 1000)
+}
+
+#[test]
+fn every_supported_scheme_signs_verifiably() {
+    for scheme in SUPPORTED_SCHEMES {
+        let mut keystore = InMemKeystore::default();
+        let (address, _, _) = keystore.generate_and_add_new_key(scheme, None, None, None).unwrap();
+        let signer = Signer::InMemory(keystore);
+        let tx_data = tx_data1(address);
+
+        assert_eq!(signer.scheme(&address).unwrap(), scheme);
+        let signature = signer.sign(&address, &tx_data).unwrap();
+        signature
+            .verify_secure(&IntentMessage::new(Intent::iota_transaction(), tx_data), address, scheme)
+            .unwrap_or_else(|e| panic!("{scheme} signature does not verify: {e}"));
+    }
+}
+
+#[test]
+fn unknown_address_is_a_signing_error() {
+    let signer = Signer::InMemory(InMemKeystore::new_insecure_for_tests(1));
+    let stranger = IotaAddress::random_for_testing_only();
+    assert!(signer.sign(&stranger, &tx_data1(stranger)).is_err());
+}
+
+#[test]
+fn missing_keystore_file_names_the_path() {
+    let path = std::env::temp_dir().join("iota-challenge3-no-such-dir").join("iota.keystore");
+    match Signer::from_file(&path) {
+        Err(error @ ChallengeError::KeystoreNotFound { .. }) => {
+            let message = error.to_string();
+            assert!(message.contains(&path.display().to_string()), "{message}");
+            assert!(message.contains("iota client"), "{message}");
+        }
+        Err(other) => panic!("expected KeystoreNotFound, got {other}"),
+        Ok(_) => panic!("expected KeystoreNotFound, got a keystore"),
+    }
+}
+
+#[test]
+fn transaction_signed_offline_round_trips_through_files() {
+    let signer = Signer::InMemory(InMemKeystore::new_insecure_for_tests(1));
+    let sender = signer.addresses()[0];
+    let dir = std::env::temp_dir().join(format!("iota-challenge3-offline-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (tx_file, sig_file) = (dir.join("tx1.bcs"), dir.join("tx1.sig"));
+
+    // Online: build and write. Offline: read, sign and write the signature. Online: read both back.
+    let built = tx_data1(sender);
+    offline::write_bcs(&tx_file, &built).unwrap();
+    let tx_data: TransactionData = offline::read_bcs(&tx_file).unwrap();
+    offline::write_bcs(&sig_file, &signer.sign_transaction(&tx_data).unwrap()).unwrap();
+    let signatures: Vec<Signature> = offline::read_bcs(&sig_file).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(tx_data, built);
+    assert_eq!(signatures.len(), 1);
+    signatures[0]
+        .verify_secure(&IntentMessage::new(Intent::iota_transaction(), tx_data), sender, SignatureScheme::ED25519)
+        .unwrap();
+}
+
+#[test]
+fn watch_only_signer_builds_but_never_signs() {
+    let sender = IotaAddress::random_for_testing_only();
+    let signer = Signer::WatchOnly(sender);
+
+    assert_eq!(signer.addresses(), [sender]);
+    assert!(matches!(
+        signer.sign(&sender, &tx_data1(sender)),
+        Err(ChallengeError::WatchOnly(address)) if address == sender
+    ));
+}
+
+#[test]
+fn sponsored_transaction_is_signed_by_sender_and_sponsor() {
+    let signer = Signer::InMemory(InMemKeystore::new_insecure_for_tests(2));
+    let (sender, sponsor) = (signer.addresses()[0], signer.addresses()[1]);
+    let gas = GasConfig {
+        sponsor: Some(sponsor),
+        ..GasConfig::estimated(1000)
+    };
+    let pt = mint_ptb(&deployment(), shared_treasury_cap(), 3, &HumanReporter).unwrap();
+    let tx_data = gas.transaction(sender, coin(GAS_COIN_TYPE, 1, 1_000_000_000).object_ref(), pt);
+
+    let signatures = signer.sign_transaction(&tx_data).unwrap();
+
+    assert_eq!(signatures.len(), 2);
+    for (signature, signer) in signatures.iter().zip([sender, sponsor]) {
+        let message = IntentMessage::new(Intent::iota_transaction(), tx_data.clone());
+        signature.verify_secure(&message, signer, SignatureScheme::ED25519).unwrap();
+    }
+}
+
+#[test]
+fn several_keystores_sign_with_the_one_holding_the_key() {
+    let first = InMemKeystore::new_insecure_for_tests(1);
+    let second = InMemKeystore::new_insecure_for_tests(2);
+    let sender = second.addresses()[1];
+    let signer = Signer::Many(vec![Signer::InMemory(first), Signer::InMemory(second)]);
+    assert_eq!(signer.addresses().len(), 3);
+
+    let tx_data = tx_data1(sender);
+    let signature = signer.sign(&sender, &tx_data).unwrap();
+    let scheme = signer.scheme(&sender).unwrap();
+    signature
+        .verify_secure(&IntentMessage::new(Intent::iota_transaction(), tx_data), sender, scheme)
+        .unwrap();
+
+    let stranger = IotaAddress::random_for_testing_only();
+    assert!(signer.sign(&stranger, &tx_data1(stranger)).is_err());
+}